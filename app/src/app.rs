@@ -28,7 +28,15 @@ pub struct XY {
     pub y: Y,
 }
 
+pub mod cargo;
+pub mod csv;
 pub mod draw;
+pub mod file;
+pub mod history;
+mod input;
+pub mod report;
+pub mod rust_enum;
+mod text;
 
 pub use draw::{
     DrawLength,
@@ -42,13 +50,35 @@ pub use draw::{
     SpriteSpec,
     Sizes,
 };
+pub use history::{History, UNDO_LIMIT};
+pub use input::{EyeMovement, KeyRepeat};
+use input::{HeldDirections, Input};
+pub use text::{
+    TextInput,
+    TextKeys,
+    TEXT_LEFT,
+    TEXT_RIGHT,
+    TEXT_HOME,
+    TEXT_END,
+    TEXT_SHIFT_DOWN,
+    TEXT_CTRL_DOWN,
+    TEXT_SELECT_ALL,
+    TEXT_COPY,
+    TEXT_CUT,
+    TEXT_TAB,
+    TEXT_ENTER,
+    TEXT_ESCAPE,
+};
+use core::ops::RangeInclusive;
 use draw::{
     draw_xy_from_tile,
     tile_xy_from_draw,
     label_wh,
+    tile_draw_wh,
+    ui_tile_draw_wh,
     top_label_rect,
     left_label_rect,
-    zero_tile_xy,
+    center_ui_xy,
 };
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
@@ -86,7 +116,9 @@ pub enum NineSlice {
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum BorderKind {
     WhiteEdge,
-    YellowEdge
+    YellowEdge,
+    /// Thicker than the others, to mark where typing goes.
+    BlueEdge,
 }
 
 impl Default for BorderKind {
@@ -120,43 +152,92 @@ mod tile {
 
     pub type Coord = u8;
 
-    pub(crate) const COORD_MAX: Coord = 0b1111;
-    pub(crate) const COORD_COUNT: Coord = COORD_MAX + 1;
+    /// The fewest tiles there can be along either axis.
+    pub const COUNT_MIN: Count = 3;
+    /// The most tiles there can be along either axis. Chosen so that the tiles
+    /// stay a few pixels wide on a 1080p screen.
+    pub const COUNT_MAX: Count = 200;
+
+    // This would need to change if `COUNT_MAX` were raised far enough.
+    const _: () = assert!(COUNT_MAX <= Coord::MAX as Count + 1);
+
+    pub const COUNT_DEFAULT: Count = 16;
+
+    /// The number of tiles along each axis.
+    #[derive(Clone, Copy, Debug, PartialEq, Eq)]
+    pub struct WH {
+        w: Count,
+        h: Count,
+    }
+
+    impl Default for WH {
+        fn default() -> Self {
+            Self {
+                w: COUNT_DEFAULT,
+                h: COUNT_DEFAULT,
+            }
+        }
+    }
+
+    type WHError = ();
+
+    impl WH {
+        pub fn new(w: Count, h: Count) -> Result<Self, WHError> {
+            let range = COUNT_MIN..=COUNT_MAX;
+            if range.contains(&w) && range.contains(&h) {
+                Ok(Self { w, h })
+            } else {
+                Err(())
+            }
+        }
+
+        pub fn w(self) -> Count {
+            self.w
+        }
+
+        pub fn h(self) -> Count {
+            self.h
+        }
+
+        pub fn len(self) -> usize {
+            self.w as usize * self.h as usize
+        }
+
+        fn max_x(self) -> Coord {
+            (self.w - 1) as Coord
+        }
+
+        fn max_y(self) -> Coord {
+            (self.h - 1) as Coord
+        }
+    }
 
     #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     pub struct X(Coord);
 
-    impl X {
-        pub const MAX: Coord = COORD_MAX;
-        pub const COUNT: Count = (X::MAX as Count) + 1;
+    type XError = ();
 
-        pub fn saturating_add_one(&self) -> Self {
-            Self(core::cmp::min(self.0.saturating_add(1), Self::MAX))
+    impl X {
+        pub fn saturating_add_one(&self, wh: WH) -> Self {
+            Self(core::cmp::min(self.0.saturating_add(1), wh.max_x()))
         }
 
         pub fn saturating_sub_one(&self) -> Self {
             Self(self.0.saturating_sub(1))
         }
-    }
-
-    type XError = ();
 
-    impl TryFrom<DrawX> for X {
-        type Error = XError;
-        fn try_from(draw_x: DrawX) -> Result<Self, Self::Error> {
-            if draw_x >= 0. && draw_x < (Self::MAX + 1) as DrawX {
-                Ok(Self(draw_x as Coord))
+        pub fn try_from_coord(coord: Coord, wh: WH) -> Result<Self, XError> {
+            if coord <= wh.max_x() {
+                Ok(Self(coord))
             } else {
                 Err(())
             }
         }
-    }
 
-    impl TryFrom<Coord> for X {
-        type Error = XError;
-        fn try_from(coord: Coord) -> Result<Self, Self::Error> {
-            if coord <= Self::MAX {
-                Ok(Self(coord))
+        /// `draw_x` is in units of tiles, relative to the left of the board.
+        pub fn try_from_draw(draw_x: DrawX, wh: WH) -> Result<Self, XError> {
+            if draw_x >= 0. && draw_x < wh.w as DrawX {
+                Ok(Self(draw_x as Coord))
             } else {
                 Err(())
             }
@@ -172,37 +253,29 @@ mod tile {
     #[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
     pub struct Y(Coord);
 
-    impl Y {
-        pub const MAX: Coord = COORD_MAX;
-        pub const COUNT: Count = (Y::MAX as Count) + 1;
+    type YError = ();
 
-        pub fn saturating_add_one(&self) -> Self {
-            Self(core::cmp::min(self.0.saturating_add(1), Self::MAX))
+    impl Y {
+        pub fn saturating_add_one(&self, wh: WH) -> Self {
+            Self(core::cmp::min(self.0.saturating_add(1), wh.max_y()))
         }
 
         pub fn saturating_sub_one(&self) -> Self {
             Self(self.0.saturating_sub(1))
         }
-    }
-
-    type YError = ();
 
-    impl TryFrom<DrawX> for Y {
-        type Error = YError;
-        fn try_from(draw_y: DrawY) -> Result<Self, Self::Error> {
-            if draw_y >= 0. && draw_y < (Self::MAX + 1) as DrawY {
-                Ok(Self(draw_y as Coord))
+        pub fn try_from_coord(coord: Coord, wh: WH) -> Result<Self, YError> {
+            if coord <= wh.max_y() {
+                Ok(Self(coord))
             } else {
                 Err(())
             }
         }
-    }
 
-    impl TryFrom<Coord> for Y {
-        type Error = YError;
-        fn try_from(coord: Coord) -> Result<Self, Self::Error> {
-            if coord <= Self::MAX {
-                Ok(Self(coord))
+        /// `draw_y` is in units of tiles, relative to the top of the board.
+        pub fn try_from_draw(draw_y: DrawY, wh: WH) -> Result<Self, YError> {
+            if draw_y >= 0. && draw_y < wh.h as DrawY {
+                Ok(Self(draw_y as Coord))
             } else {
                 Err(())
             }
@@ -222,42 +295,55 @@ mod tile {
     }
 
     impl XY {
-        pub const COUNT: Count = X::COUNT * Y::COUNT;
+        /// The indexes used by `Board`'s methods.
+        pub fn column_and_row(self) -> (usize, usize) {
+            (usize::from(Coord::from(self.x)), usize::from(Coord::from(self.y)))
+        }
+
+        /// The inverse of `column_and_row`. Returns `None` if the position is
+        /// outside a board of the given size.
+        pub fn from_column_and_row(
+            (column, row): (usize, usize),
+            wh: WH
+        ) -> Option<Self> {
+            Some(Self {
+                x: X::try_from_coord(Coord::try_from(column).ok()?, wh).ok()?,
+                y: Y::try_from_coord(Coord::try_from(row).ok()?, wh).ok()?,
+            })
+        }
 
         pub fn move_up(&mut self) {
             self.y = self.y.saturating_sub_one();
         }
 
-        pub fn move_down(&mut self) {
-            self.y = self.y.saturating_add_one();
+        pub fn move_down(&mut self, wh: WH) {
+            self.y = self.y.saturating_add_one(wh);
         }
 
         pub fn move_left(&mut self) {
             self.x = self.x.saturating_sub_one();
         }
 
-        pub fn move_right(&mut self) {
-            self.x = self.x.saturating_add_one();
+        pub fn move_right(&mut self, wh: WH) {
+            self.x = self.x.saturating_add_one(wh);
         }
     }
 
-    #[allow(unused)]
-    pub fn xy_to_i(xy: XY) -> usize {
-        xy_to_i_usize((usize::from(xy.x.0), usize::from(xy.y.0)))
+    pub fn xy_to_i(xy: XY, wh: WH) -> usize {
+        xy_to_i_usize((usize::from(xy.x.0), usize::from(xy.y.0)), wh)
     }
 
-    pub fn xy_to_i_usize((x, y): (usize, usize)) -> usize {
-        y * Y::COUNT as usize + x
+    pub fn xy_to_i_usize((x, y): (usize, usize), wh: WH) -> usize {
+        y * wh.w as usize + x
     }
 
-    pub fn i_to_xy(index: usize) -> XY {
+    pub fn i_to_xy(index: usize, wh: WH) -> XY {
         XY {
             x: X(to_coord_or_default(
-                (index % X::COUNT as usize) as Count
+                (index % wh.w as usize) as Count
             )),
             y: Y(to_coord_or_default(
-                ((index % (XY::COUNT as usize) as usize)
-                / X::COUNT as usize) as Count
+                ((index % wh.len()) / wh.w as usize) as Count
             )),
         }
     }
@@ -267,11 +353,11 @@ mod tile {
     }
 
     #[cfg(test)]
-    pub fn all_xys() -> Vec<XY> {
-        let mut output = Vec::with_capacity(XY::COUNT as usize);
+    pub fn all_xys(wh: WH) -> Vec<XY> {
+        let mut output = Vec::with_capacity(wh.len());
 
-        for y in 0..Y::MAX {
-            for x in 0..X::MAX {
+        for y in 0..=wh.max_y() {
+            for x in 0..=wh.max_x() {
                 output.push(XY {x: X(x), y: Y(y)});
             }
         }
@@ -290,10 +376,16 @@ mod cell {
         Pressed
     }
 
+    /// The variants are declared in the order that clicking a tile cycles
+    /// through them.
     #[derive(Copy, Clone, Debug, Eq, PartialEq)]
-    pub(crate) enum Status {
+    pub enum Status {
         Unchecked,
-        Checked,
+        Passes,
+        Fails,
+        Blocked,
+        NeedsRecheck,
+        NotApplicable,
     }
 
     impl Default for Status {
@@ -303,31 +395,132 @@ mod cell {
     }
 
     impl Status {
+        /// Every status, in cycle order. A new status needs to be added here,
+        /// and given a sprite, a name, and a character in the file format.
+        pub const ALL: [Self; 6] = [
+            Self::Unchecked,
+            Self::Passes,
+            Self::Fails,
+            Self::Blocked,
+            Self::NeedsRecheck,
+            Self::NotApplicable,
+        ];
+
         pub(crate) fn sprite_fn(self) -> fn(UiState) -> SpriteKind {
             match self {
                 Self::Unchecked => SpriteKind::Unchecked,
-                Self::Checked => SpriteKind::Checked,
+                Self::Passes => SpriteKind::Passes,
+                Self::Fails => SpriteKind::Fails,
+                Self::Blocked => SpriteKind::Blocked,
+                Self::NeedsRecheck => SpriteKind::NeedsRecheck,
+                Self::NotApplicable => SpriteKind::NotApplicable,
+            }
+        }
+
+        /// The position of this status in `ALL`.
+        pub fn index(self) -> usize {
+            self as usize
+        }
+
+        /// The next status in the cycle, wrapping around to the start.
+        pub fn next(self) -> Self {
+            Self::ALL[(self.index() + 1) % Self::ALL.len()]
+        }
+
+        /// The previous status in the cycle, wrapping around to the end.
+        pub fn previous(self) -> Self {
+            Self::ALL[(self.index() + Self::ALL.len() - 1) % Self::ALL.len()]
+        }
+
+        /// Whether the tile has been looked at and has a finding. Blocked tiles
+        /// and those that need a recheck still need looking at.
+        pub fn is_checked(self) -> bool {
+            match self {
+                Self::Passes | Self::Fails | Self::NotApplicable => true,
+                Self::Unchecked | Self::Blocked | Self::NeedsRecheck => false,
             }
         }
+
+        /// A name for the status with no spaces, for use on the command line.
+        pub fn name(self) -> &'static str {
+            match self {
+                Self::Unchecked => "unchecked",
+                Self::Passes => "passes",
+                Self::Fails => "fails",
+                Self::Blocked => "blocked",
+                Self::NeedsRecheck => "needs-recheck",
+                Self::NotApplicable => "not-applicable",
+            }
+        }
+
+        /// A name for the status to show to people.
+        pub fn display_name(self) -> &'static str {
+            match self {
+                Self::Unchecked => "Unchecked",
+                Self::Passes => "Passes",
+                Self::Fails => "Fails",
+                Self::Blocked => "Blocked",
+                Self::NeedsRecheck => "Needs recheck",
+                Self::NotApplicable => "Not applicable",
+            }
+        }
+
+        pub fn from_name(name: &str) -> Option<Self> {
+            Self::ALL.iter().copied().find(|status| status.name() == name)
+        }
+    }
+
+    #[test]
+    fn all_the_statuses_are_in_all_in_declaration_order() {
+        for (i, status) in Status::ALL.iter().enumerate() {
+            assert_eq!(status.index(), i);
+            assert_eq!(status.next().previous(), *status);
+        }
     }
 }
-pub use cell::UiState;
+pub use cell::{Status, UiState};
+
+/// How many tiles have each status.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct StatusCounts([usize; Status::ALL.len()]);
+
+impl StatusCounts {
+    pub fn get(&self, status: Status) -> usize {
+        self.0[status.index()]
+    }
 
-type TileData = cell::Status;
+    /// Each status, in cycle order, with its count.
+    pub fn iter(&self) -> impl Iterator<Item = (Status, usize)> + '_ {
+        Status::ALL.iter().map(move |&status| (status, self.get(status)))
+    }
+}
 
-pub const TILES_LENGTH: usize = tile::XY::COUNT as _;
+/// Free-form text about a pair. Empty notes are the same as no note.
+pub type Note = String;
 
-type TileDataArray = [TileData; TILES_LENGTH as _];
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+struct TileData {
+    status: Status,
+    note: Note,
+}
 
 #[derive(Clone, Debug)]
 pub struct Tiles {
-    tiles: TileDataArray,
+    wh: tile::WH,
+    tiles: Vec<TileData>,
 }
 
 impl Default for Tiles {
     fn default() -> Self {
+        Self::new(<_>::default())
+    }
+}
+
+impl Tiles {
+    fn new(wh: tile::WH) -> Self {
         Self {
-            tiles: [TileData::default(); TILES_LENGTH as _],
+            wh,
+            tiles: vec![TileData::default(); wh.len()],
         }
     }
 }
@@ -387,16 +580,188 @@ impl Default for ButtonState {
 
 pub type CursorXY = DrawXY;
 
+/// Which set of labels something refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Axis {
+    /// The labels along the top, one per column.
+    Columns,
+    /// The labels along the left side, one per row.
+    Rows,
+}
+
+/// A rectangle of tiles, from the tile where the selection started to the
+/// opposite corner, inclusive.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+struct Selection {
+    anchor: tile::XY,
+    corner: tile::XY,
+}
+
+impl Selection {
+    fn ranges(self) -> (RangeInclusive<ColumnIndex>, RangeInclusive<RowIndex>) {
+        let (anchor_column, anchor_row) = self.anchor.column_and_row();
+        let (corner_column, corner_row) = self.corner.column_and_row();
+
+        (
+            anchor_column.min(corner_column)..=anchor_column.max(corner_column),
+            anchor_row.min(corner_row)..=anchor_row.max(corner_row),
+        )
+    }
+
+    /// All of the column, or row, with the given index.
+    fn line(axis: Axis, index: usize, wh: tile::WH) -> Option<Self> {
+        let (first, last) = match axis {
+            Axis::Columns => ((index, 0), (index, wh.h() as usize - 1)),
+            Axis::Rows => ((0, index), (wh.w() as usize - 1, index)),
+        };
+
+        Some(Self {
+            anchor: tile::XY::from_column_and_row(first, wh)?,
+            corner: tile::XY::from_column_and_row(last, wh)?,
+        })
+    }
+
+    fn contains(self, txy: tile::XY) -> bool {
+        let (columns, rows) = self.ranges();
+        let (column, row) = txy.column_and_row();
+
+        columns.contains(&column) && rows.contains(&row)
+    }
+}
+
+/// What dragging with the left mouse button held down is doing.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Drag {
+    /// Sets each tile the cursor passes over to `status`, which is the status
+    /// after the one the `from` tile had. Nothing is set until the cursor
+    /// leaves the `from` tile, so that a plain click still acts as a click.
+    /// `last` is the tile painted most recently, so the tiles between it and
+    /// the cursor can be filled in when the cursor moves more than one tile
+    /// in a frame.
+    Paint { from: tile::XY, last: tile::XY, status: Status, started: bool },
+    /// Moves the corner of the selection to follow the cursor.
+    Select,
+}
+
+/// The column and row of each tile on a straight line from `start` to `end`,
+/// including both, with each one touching the one before it, at least on a
+/// corner.
+fn tiles_on_line(
+    start: (ColumnIndex, RowIndex),
+    end: (ColumnIndex, RowIndex),
+) -> Vec<(ColumnIndex, RowIndex)> {
+    // Bresenham's line algorithm.
+    let (end_x, end_y) = (end.0 as isize, end.1 as isize);
+    let (mut x, mut y) = (start.0 as isize, start.1 as isize);
+
+    let dx = (end_x - x).abs();
+    let dy = -(end_y - y).abs();
+    let step_x = if x < end_x { 1 } else { -1 };
+    let step_y = if y < end_y { 1 } else { -1 };
+    let mut error = dx + dy;
+
+    let mut tiles = Vec::with_capacity(dx.max(-dy) as usize + 1);
+    loop {
+        tiles.push((x as ColumnIndex, y as RowIndex));
+        if x == end_x && y == end_y {
+            break;
+        }
+
+        let doubled = 2 * error;
+        if doubled >= dy {
+            error += dy;
+            x += step_x;
+        }
+        if doubled <= dx {
+            error += dx;
+            y += step_y;
+        }
+    }
+
+    tiles
+}
+
 #[derive(Debug, Eq, PartialEq)]
 enum ClickArea {
     TileXY(tile::XY),
-    Labels,
+    Label(Axis, usize),
+    EditLabelsButton(Axis),
+    PreviousLabelPage,
+    NextLabelPage,
+    LineButton(LineButton),
 }
 
-#[derive(Debug)]
+/// The buttons shown next to the focused label, which change its whole line.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum LineButton {
+    InsertBefore,
+    InsertAfter,
+    MoveBack,
+    MoveForward,
+    Remove,
+}
+
+impl LineButton {
+    const ALL: [Self; 5] = [
+        Self::InsertBefore,
+        Self::InsertAfter,
+        Self::MoveBack,
+        Self::MoveForward,
+        Self::Remove,
+    ];
+
+    fn text(self, axis: Axis) -> &'static str {
+        use LineButton::*;
+        match (self, axis) {
+            (InsertBefore, Axis::Columns) => "Insert left",
+            (InsertBefore, Axis::Rows) => "Insert above",
+            (InsertAfter, Axis::Columns) => "Insert right",
+            (InsertAfter, Axis::Rows) => "Insert below",
+            (MoveBack, Axis::Columns) => "Move left",
+            (MoveBack, Axis::Rows) => "Move up",
+            (MoveForward, Axis::Columns) => "Move right",
+            (MoveForward, Axis::Rows) => "Move down",
+            (Remove, Axis::Columns) => "Remove column",
+            (Remove, Axis::Rows) => "Remove row",
+        }
+    }
+
+    /// What pressing the button does to the line at `index`, out of `count`
+    /// lines, and where the label that should be focused afterwards ends up.
+    /// Returns `None` if the button would not do anything.
+    fn op(self, index: usize, count: usize) -> Option<(LineOp, usize)> {
+        use LineButton::*;
+        match self {
+            InsertBefore | InsertAfter if count >= LABEL_COUNT_MAX => None,
+            InsertBefore => Some((
+                LineOp::Insert { index, label: Label::new() },
+                index,
+            )),
+            InsertAfter => Some((
+                LineOp::Insert { index: index + 1, label: Label::new() },
+                index + 1,
+            )),
+            MoveBack => index.checked_sub(1)
+                .map(|to| (LineOp::Move { from: index, to }, to)),
+            MoveForward => Some(index + 1)
+                .filter(|&to| to < count)
+                .map(|to| (LineOp::Move { from: index, to }, to)),
+            Remove if count <= LABEL_COUNT_MIN => None,
+            // Focus the label that takes its place, so several lines can be
+            // removed in a row.
+            Remove => Some((
+                LineOp::Remove { index },
+                index.min(count - 2),
+            )),
+        }
+    }
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
 enum UiMode {
     Checking,
-    EditLabels,
+    EditLabels(Axis),
+    EditNote(tile::XY),
 }
 
 impl Default for UiMode {
@@ -408,13 +773,63 @@ impl Default for UiMode {
 #[derive(Debug, Default)]
 pub struct Ui {
     mode: UiMode,
+    eye_movement: EyeMovement,
+    held_directions: HeldDirections,
     left_mouse_button: ButtonState,
     sizes: draw::Sizes,
     cursor_xy: CursorXY,
     last_pressed: Option<ClickArea>,
+    drag: Option<Drag>,
+    selection: Option<Selection>,
+    /// Which group of `LABELS_PER_PAGE` labels is shown in `EditLabels` mode.
+    label_page: usize,
+    /// The label text box that typing goes into, if any.
+    focused_label: Option<FocusedLabel>,
+    /// Where the caret is in the note being edited in `EditNote` mode. The
+    /// caret stays at the end of the note, but the whole note can be
+    /// selected.
+    note_cursor: text::Cursor,
+    /// Text that should be put on the clipboard. See `State::take_copied_text`.
+    copied_text: Option<String>,
+}
+
+/// How long the caret stays shown, and then hidden, when blinking.
+const CARET_BLINK_FRAMES: u16 = 30;
+
+/// Whether a blinking caret is shown, `frames` frames after it started
+/// blinking.
+fn is_caret_shown(frames: u16) -> bool {
+    (frames / CARET_BLINK_FRAMES).is_multiple_of(2)
+}
+
+/// The label being edited, and the caret and the selection in it.
+#[derive(Clone, Debug)]
+struct FocusedLabel {
+    axis: Axis,
+    index: usize,
+    /// What the label was when it was focused, to go back to if the edit is
+    /// cancelled.
+    original: Label,
+    cursor: text::Cursor,
+    /// Whether the mouse was pressed inside the label, and is still down, so
+    /// moving it changes the selection.
+    dragging: bool,
+    /// How long it has been since the caret moved. The caret stays shown
+    /// while it is moving, and blinks after that.
+    idle_frames: u16,
+}
+
+impl FocusedLabel {
+    fn is_caret_shown(&self) -> bool {
+        is_caret_shown(self.idle_frames)
+    }
 }
 
 impl Ui {
+    fn is_selected(&self, txy: tile::XY) -> bool {
+        self.selection.is_some_and(|selection| selection.contains(txy))
+    }
+
     fn tile_state(&self, txy: tile::XY) -> UiState {
         use ClickArea::*;
         match self.last_pressed {
@@ -429,7 +844,7 @@ impl Ui {
                     (true, ButtonState::Down) => UiState::Pressed,
                 }
             }
-            None | Some(Labels) => {
+            None | Some(_) => {
                 match (self.is_hovered(TileXY(txy)), self.left_mouse_button) {
                     (false, _) => UiState::Idle,
                     (true, ButtonState::Up) => UiState::Hover,
@@ -452,31 +867,219 @@ impl Ui {
                     max_y: xy.y + self.sizes.tile_side_length,
                 }
             },
-            Labels => top_label_rect(&self.sizes),
+            Label(axis, index) => draw::label_rect(&self.sizes, axis, index),
+            EditLabelsButton(axis) => edit_labels_button_rect(&self.sizes, axis),
+            PreviousLabelPage => label_page_button_rect(&self.sizes, Dir::Up),
+            NextLabelPage => label_page_button_rect(&self.sizes, Dir::Down),
+            LineButton(button) => line_button_rect(&self.sizes, button),
         };
 
         rect.contains(self.cursor_xy)
     }
 
-    fn click_area(&self) -> Option<ClickArea> {
-        if top_label_rect(&self.sizes).contains(self.cursor_xy) {
-            return Some(ClickArea::Labels);
+    /// The line buttons that would do something to the focused label's line,
+    /// if any.
+    fn usable_line_buttons<'board>(
+        &self,
+        board: &'board Board,
+    ) -> impl Iterator<Item = LineButton> + 'board {
+        let focused = self.focused_label.as_ref()
+            .map(|f| (f.index, board.labels(f.axis).len()));
+
+        LineButton::ALL.into_iter()
+            .filter(move |button| {
+                focused.is_some_and(|(index, count)| button.op(index, count).is_some())
+            })
+    }
+
+    fn click_area(&self, board: &Board) -> Option<ClickArea> {
+        for axis in [Axis::Columns, Axis::Rows] {
+            if let Some(index) = draw::label_index_from_draw(&self.sizes, axis, self.cursor_xy) {
+                return Some(ClickArea::Label(axis, index));
+            }
+
+            if self.is_hovered(ClickArea::EditLabelsButton(axis)) {
+                return Some(ClickArea::EditLabelsButton(axis));
+            }
+        }
+
+        if let UiMode::EditLabels(_) = self.mode {
+            if self.is_hovered(ClickArea::PreviousLabelPage) {
+                return Some(ClickArea::PreviousLabelPage);
+            }
+
+            if self.is_hovered(ClickArea::NextLabelPage) {
+                return Some(ClickArea::NextLabelPage);
+            }
+
+            if let Some(button) = self.usable_line_buttons(board)
+                .find(|&button| self.is_hovered(ClickArea::LineButton(button))) {
+                return Some(ClickArea::LineButton(button));
+            }
         }
 
         tile_xy_from_draw(&self.sizes, self.cursor_xy)
+            .filter(|&txy| {
+                let (column, row) = txy.column_and_row();
+                board.is_interactive(column, row)
+            })
             .map(ClickArea::TileXY)
     }
 }
 
-type Label = String;
+pub type Label = String;
+
+/// The fewest labels a board can have.
+pub const LABEL_COUNT_MIN: usize = tile::COUNT_MIN as _;
+/// The most labels a board can have.
+pub const LABEL_COUNT_MAX: usize = tile::COUNT_MAX as _;
+/// How many labels a default board has.
+pub const LABEL_COUNT_DEFAULT: usize = tile::COUNT_DEFAULT as _;
+
+/// How `parse_label_list` treats each line.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct LabelListOptions {
+    /// Remove whitespace from the start and end of each label.
+    pub trim: bool,
+    /// Skip lines that start with a `#`, after any whitespace.
+    pub skip_comments: bool,
+    /// Skip lines that are empty, or only whitespace. Without this, they
+    /// become empty labels.
+    pub skip_blank: bool,
+}
 
-const LABEL_COUNT: usize = tile::COORD_COUNT as usize;
+/// Splits `text` into labels, one per line, for lists of labels kept in
+/// files.
+pub fn parse_label_list(text: &str, options: LabelListOptions) -> Vec<Label> {
+    text.lines()
+        .filter(|line| !(options.skip_blank && line.trim().is_empty()))
+        .filter(|line| !(options.skip_comments && line.trim_start().starts_with('#')))
+        .map(|line| if options.trim { line.trim() } else { line }.to_owned())
+        .collect()
+}
 
-#[derive(Debug, Default)]
-struct Board {
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct LabelCountError {
+    pub axis: Axis,
+    pub count: usize,
+}
+
+impl core::fmt::Display for LabelCountError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(
+            f,
+            "a board needs between {LABEL_COUNT_MIN} and {LABEL_COUNT_MAX} {} labels, not {}",
+            match self.axis {
+                Axis::Columns => "column",
+                Axis::Rows => "row",
+            },
+            self.count
+        )
+    }
+}
+
+impl std::error::Error for LabelCountError {}
+
+/// How the tiles that mirror other tiles are shown on a symmetric board.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MirrorTiles {
+    Dimmed,
+    /// Hidden tiles cannot be clicked either.
+    Hidden,
+}
+
+/// One half of a square board, split along the diagonal from the top left to
+/// the bottom right.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Triangle {
+    /// The tiles right of the diagonal.
+    Upper,
+    /// The tiles left of the diagonal.
+    Lower,
+}
+
+/// What to do with the tiles on the diagonal of a symmetric board, which pair
+/// each label with itself.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Diagonal {
+    /// The tiles are shown and counted like any other.
+    Included,
+    /// The tiles are neither shown nor counted.
+    Excluded,
+    /// The tiles are shown as not applicable, and not counted.
+    NotApplicable,
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SymmetricLayout {
+    /// The half of the board that is counted, and always shown. The tiles in
+    /// the other half mirror these ones.
+    pub kept: Triangle,
+    pub mirror_tiles: MirrorTiles,
+    pub diagonal: Diagonal,
+}
+
+impl Default for SymmetricLayout {
+    fn default() -> Self {
+        Self {
+            kept: Triangle::Lower,
+            mirror_tiles: MirrorTiles::Dimmed,
+            diagonal: Diagonal::Included,
+        }
+    }
+}
+
+/// Whether the tile in column a, row b means the same thing as the one in
+/// column b, row a.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum Symmetry {
+    #[default]
+    Asymmetric,
+    /// Each tile in the kept triangle is mirrored by the tile in the
+    /// transposed position, so changing either changes both, and the pair is
+    /// only counted once.
+    Symmetric(SymmetricLayout),
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum TileRole {
+    Counted,
+    Mirror(MirrorTiles),
+    ExcludedDiagonal,
+    NotApplicableDiagonal,
+}
+
+/// Only square boards can be symmetric.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotSquare;
+
+impl core::fmt::Display for NotSquare {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        write!(f, "only boards with as many columns as rows can be symmetric")
+    }
+}
+
+impl std::error::Error for NotSquare {}
+
+#[derive(Debug)]
+pub struct Board {
     tiles: Tiles,
-    labels: [Label; LABEL_COUNT],
+    column_labels: Vec<Label>,
+    row_labels: Vec<Label>,
     eye: Eye,
+    symmetry: Symmetry,
+}
+
+impl Default for Board {
+    fn default() -> Self {
+        Self {
+            tiles: Tiles::default(),
+            column_labels: vec![Label::default(); LABEL_COUNT_DEFAULT],
+            row_labels: vec![Label::default(); LABEL_COUNT_DEFAULT],
+            eye: Eye::default(),
+            symmetry: Symmetry::default(),
+        }
+    }
 }
 
 /// 64k animation frames ought to be enough for anybody!
@@ -489,124 +1092,1669 @@ const ANIMATION_TIMER_LENGTH: AnimationTimer = 60 * 60 * 18;
 pub struct State {
     ui: Ui,
     board: Board,
+    history: History,
     animation_timer: AnimationTimer
 }
 
-pub fn sizes(state: &State) -> draw::Sizes {
-    state.ui.sizes.clone()
+/// An index into the columns of a board, counting from the left.
+pub type ColumnIndex = usize;
+/// An index into the rows of a board, counting from the top.
+pub type RowIndex = usize;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Progress {
+    /// How many tiles have a status where `Status::is_checked` is true.
+    pub checked: usize,
+    pub total: usize,
+    pub counts: StatusCounts,
 }
 
-pub type InputFlags = u16;
-
-pub const INPUT_UP_PRESSED: InputFlags              = 0b0000_0000_0000_0001;
-pub const INPUT_DOWN_PRESSED: InputFlags            = 0b0000_0000_0000_0010;
-pub const INPUT_LEFT_PRESSED: InputFlags            = 0b0000_0000_0000_0100;
-pub const INPUT_RIGHT_PRESSED: InputFlags           = 0b0000_0000_0000_1000;
-
-pub const INPUT_UP_DOWN: InputFlags                 = 0b0000_0000_0001_0000;
-pub const INPUT_DOWN_DOWN: InputFlags               = 0b0000_0000_0010_0000;
-pub const INPUT_LEFT_DOWN: InputFlags               = 0b0000_0000_0100_0000;
-pub const INPUT_RIGHT_DOWN: InputFlags              = 0b0000_0000_1000_0000;
-
-pub const INPUT_INTERACT_PRESSED: InputFlags        = 0b0000_0001_0000_0000;
-pub const INPUT_INTERACT_DOWN: InputFlags           = 0b0000_0010_0000_0000;
+impl Progress {
+    fn add(&mut self, status: Status) {
+        self.total += 1;
+        if status.is_checked() {
+            self.checked += 1;
+        }
+        self.counts.0[status.index()] += 1;
+    }
 
-/// Should be set if the mouse button was pressed or released this frame.
-pub const INPUT_LEFT_MOUSE_CHANGED: InputFlags      = 0b0000_0100_0000_0000;
-pub const INPUT_LEFT_MOUSE_DOWN: InputFlags         = 0b0000_1000_0000_0000;
-
-#[derive(Clone, Copy, Debug)]
-enum Input {
-    NoChange,
-    Dir(Dir),
-    Interact,
-}
-
-impl Input {
-    fn from_flags(flags: InputFlags) -> Self {
-        use Input::*;
-        use crate::Dir::*;
-        if INPUT_INTERACT_PRESSED & flags != 0 {
-            Interact
-        } else if (INPUT_UP_DOWN | INPUT_RIGHT_DOWN) & flags == (INPUT_UP_DOWN | INPUT_RIGHT_DOWN) {
-            Dir(UpRight)
-        } else if (INPUT_DOWN_DOWN | INPUT_RIGHT_DOWN) & flags == (INPUT_DOWN_DOWN | INPUT_RIGHT_DOWN) {
-            Dir(DownRight)
-        } else if (INPUT_DOWN_DOWN | INPUT_LEFT_DOWN) & flags == (INPUT_DOWN_DOWN | INPUT_LEFT_DOWN) {
-            Dir(DownLeft)
-        } else if (INPUT_UP_DOWN | INPUT_LEFT_DOWN) & flags == (INPUT_UP_DOWN | INPUT_LEFT_DOWN) {
-            Dir(UpRight)
-        } else if INPUT_UP_DOWN & flags != 0 {
-            Dir(Up)
-        } else if INPUT_DOWN_DOWN & flags != 0 {
-            Dir(Down)
-        } else if INPUT_LEFT_DOWN & flags != 0 {
-            Dir(Left)
-        } else if INPUT_RIGHT_DOWN & flags != 0 {
-            Dir(Right)
+    /// How much of the total is checked, from 0 to 1. Nothing to check counts
+    /// as all of it being checked.
+    pub fn fraction(&self) -> f32 {
+        if self.total == 0 {
+            1.
         } else {
-            NoChange
+            self.checked as f32 / self.total as f32
         }
     }
+
+    /// `fraction` as a whole number percentage, rounded down so that 100% is
+    /// only shown when everything is checked.
+    pub fn percent(&self) -> usize {
+        (self.checked * 100).checked_div(self.total).unwrap_or(100)
+    }
 }
 
-const TEXT_BOX_W_TILES: u8 = 15;
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct OutOfBounds;
 
-fn text_box_rect(sizes: &Sizes, i: usize) -> draw::Rect {
-    let tile_side_length = sizes.tile_side_length;
+impl Board {
+    /// Makes a board with all the tiles unchecked, with one row and one column
+    /// per label.
+    pub fn with_labels(labels: Vec<Label>) -> Result<Self, LabelCountError> {
+        Self::with_axis_labels(labels.clone(), labels)
+    }
 
-    let zero_xy = zero_tile_xy(sizes);
-    let x = zero_xy.x;
-    let y = zero_xy.y + i as DrawLength * tile_side_length;
+    /// Makes a board with all the tiles unchecked, with one column per column
+    /// label and one row per row label.
+    pub fn with_axis_labels(
+        column_labels: Vec<Label>,
+        row_labels: Vec<Label>,
+    ) -> Result<Self, LabelCountError> {
+        fn to_count(axis: Axis, labels: &[Label]) -> Result<tile::Count, LabelCountError> {
+            let count = labels.len();
+            if (LABEL_COUNT_MIN..=LABEL_COUNT_MAX).contains(&count) {
+                Ok(count as tile::Count)
+            } else {
+                Err(LabelCountError { axis, count })
+            }
+        }
 
-    let number_w = tile_side_length;
+        let w = to_count(Axis::Columns, &column_labels)?;
+        let h = to_count(Axis::Rows, &row_labels)?;
 
-    let label_x = x + number_w;
+        let wh = tile::WH::new(w, h)
+            .expect("label counts should have been checked above");
 
-    draw::Rect {
-        min_x: label_x,
-        min_y: y,
-        max_x: label_x
-               + TEXT_BOX_W_TILES as DrawLength * tile_side_length,
-        max_y: y + tile_side_length,
+        Ok(Self {
+            tiles: Tiles::new(wh),
+            column_labels,
+            row_labels,
+            eye: Eye::default(),
+            symmetry: Symmetry::default(),
+        })
     }
-}
 
-pub type AsciiByte = u8;
-
-pub type TextInput = [AsciiByte; 8];
-
-pub fn update(
-    state: &mut State,
-    commands: &mut dyn ClearableStorage<draw::Command>,
-    input_flags: InputFlags,
-    text_input: TextInput,
-    cursor_xy: CursorXY,
-    draw_wh: DrawWH,
-) {
-    use draw::{TextSpec, TextKind, Command::*};
+    pub fn labels(&self, axis: Axis) -> &[Label] {
+        match axis {
+            Axis::Columns => &self.column_labels,
+            Axis::Rows => &self.row_labels,
+        }
+    }
 
-    if draw_wh != state.ui.sizes.draw_wh {
-        state.ui.sizes = draw::fresh_sizes(draw_wh);
+    pub fn labels_mut(&mut self, axis: Axis) -> &mut [Label] {
+        match axis {
+            Axis::Columns => &mut self.column_labels,
+            Axis::Rows => &mut self.row_labels,
+        }
     }
-    state.ui.cursor_xy = cursor_xy;
 
-    let left_mouse_button_down = input_flags & INPUT_LEFT_MOUSE_DOWN != 0;
+    /// Sets the labels on the given axis from the start, in order, without
+    /// changing the size of the board. Returns the labels that did not fit,
+    /// if any.
+    pub fn fill_labels<'labels>(
+        &mut self,
+        axis: Axis,
+        labels: &'labels [Label],
+    ) -> &'labels [Label] {
+        let board_labels = self.labels_mut(axis);
+        let fitting = labels.len().min(board_labels.len());
 
-    state.ui.left_mouse_button =
-        if left_mouse_button_down {
-            ButtonState::Down
-        } else {
-            ButtonState::Up
-        };
+        board_labels[..fitting].clone_from_slice(&labels[..fitting]);
 
-    commands.clear();
+        &labels[fitting..]
+    }
 
-    let input = Input::from_flags(input_flags);
+    /// Makes the labels on the given axis match `labels`, in order, keeping the
+    /// tiles of each line whose label is still there, and moving them along
+    /// with it. Lines with a label that is not in `labels` are removed, and
+    /// new, unchecked, lines are added for the labels that are not on the
+    /// board yet. Like `edit_line`, this changes both axes of symmetric
+    /// boards.
+    pub fn sync_labels(&mut self, axis: Axis, labels: &[Label]) -> Result<(), LineEditError> {
+        if !(LABEL_COUNT_MIN..=LABEL_COUNT_MAX).contains(&labels.len()) {
+            return Err(LineEditError::LabelCount(LabelCountError {
+                axis,
+                count: labels.len(),
+            }));
+        }
 
-    use EyeState::*;
-    use Input::*;
-    use crate::Dir::*;
+        // Each of `labels` keeps the first line with its label that is not
+        // already kept, and the rest are stale.
+        let mut unclaimed: Vec<&Label> = labels.iter().collect();
+        let mut stale: Vec<usize> = Vec::new();
+        for (index, label) in self.labels(axis).iter().enumerate() {
+            match unclaimed.iter().position(|&l| l == label) {
+                Some(i) => { unclaimed.swap_remove(i); },
+                None => stale.push(index),
+            }
+        }
+
+        // Stale lines are removed before new ones are added, so the board
+        // never goes over the most lines it can have, unless that would take
+        // it under the fewest. New lines go on the end, and are moved into
+        // place below.
+        let mut new_labels = unclaimed.into_iter();
+        loop {
+            let count = self.labels(axis).len();
+
+            if let Some(&index) = stale.last().filter(|_| count > LABEL_COUNT_MIN) {
+                self.edit_line(axis, LineOp::Remove { index })?;
+                stale.pop();
+            } else if let Some(label) = new_labels.next() {
+                self.edit_line(axis, LineOp::Insert { index: count, label: label.clone() })?;
+            } else {
+                // The count of `labels` was checked above, so all the stale
+                // lines can be removed without running out.
+                debug_assert!(stale.is_empty());
+                break;
+            }
+        }
+
+        for (index, label) in labels.iter().enumerate() {
+            let from = self.labels(axis)[index..].iter()
+                .position(|l| l == label)
+                .map(|i| i + index)
+                .expect("every label should be on the board by now");
+
+            if from != index {
+                self.edit_line(axis, LineOp::Move { from, to: index })?;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Returns the index of the first label on the given axis equal to
+    /// `label`, if any.
+    pub fn label_index(&self, axis: Axis, label: &str) -> Option<usize> {
+        self.labels(axis).iter().position(|l| l == label)
+    }
+
+    fn tile_index(
+        &self,
+        column: ColumnIndex,
+        row: RowIndex
+    ) -> Result<usize, OutOfBounds> {
+        let wh = self.tiles.wh;
+        if column < wh.w() as usize && row < wh.h() as usize {
+            Ok(tile::xy_to_i_usize((column, row), wh))
+        } else {
+            Err(OutOfBounds)
+        }
+    }
+
+    pub fn status(
+        &self,
+        column: ColumnIndex,
+        row: RowIndex
+    ) -> Result<Status, OutOfBounds> {
+        self.tile_index(column, row).map(|i| self.tiles.tiles[i].status)
+    }
+
+    /// On a symmetric board, this also sets the mirrored tile.
+    pub fn set_status(
+        &mut self,
+        column: ColumnIndex,
+        row: RowIndex,
+        status: Status,
+    ) -> Result<(), OutOfBounds> {
+        for i in self.tile_and_mirror_indexes(column, row)? {
+            self.tiles.tiles[i].status = status;
+        }
+
+        Ok(())
+    }
+
+    pub fn note(
+        &self,
+        column: ColumnIndex,
+        row: RowIndex
+    ) -> Result<&str, OutOfBounds> {
+        self.tile_index(column, row).map(|i| self.tiles.tiles[i].note.as_str())
+    }
+
+    /// On a symmetric board, this also sets the note of the mirrored tile.
+    pub fn set_note(
+        &mut self,
+        column: ColumnIndex,
+        row: RowIndex,
+        note: Note,
+    ) -> Result<(), OutOfBounds> {
+        for i in self.tile_and_mirror_indexes(column, row)? {
+            self.tiles.tiles[i].note.clone_from(&note);
+        }
+
+        Ok(())
+    }
+
+    /// The index of the given tile, followed by the index of the tile that
+    /// mirrors it, on symmetric boards, if that is a different tile.
+    fn tile_and_mirror_indexes(
+        &self,
+        column: ColumnIndex,
+        row: RowIndex,
+    ) -> Result<impl Iterator<Item = usize>, OutOfBounds> {
+        let i = self.tile_index(column, row)?;
+
+        let mirrored_i = match self.symmetry {
+            Symmetry::Symmetric(_) if column != row => Some(self.tile_index(row, column)?),
+            Symmetry::Symmetric(_) | Symmetry::Asymmetric => None,
+        };
+
+        Ok(core::iter::once(i).chain(mirrored_i))
+    }
+
+    pub fn symmetry(&self) -> Symmetry {
+        self.symmetry
+    }
+
+    /// Making an asymmetric board symmetric copies the status of each kept
+    /// tile onto its mirror tile. If the kept tile is unchecked, the mirror
+    /// tile's status is copied the other way instead, so checks in either
+    /// triangle are not lost. Notes are handled the same way, with empty
+    /// notes in place of unchecked tiles.
+    pub fn set_symmetry(&mut self, symmetry: Symmetry) -> Result<(), NotSquare> {
+        let wh = self.tiles.wh;
+
+        if let Symmetry::Symmetric(layout) = symmetry {
+            if wh.w() != wh.h() {
+                return Err(NotSquare);
+            }
+
+            if let Symmetry::Asymmetric = self.symmetry {
+                for (column, row) in self.upper_pairs() {
+                    let upper_i = tile::xy_to_i_usize((column, row), wh);
+                    let lower_i = tile::xy_to_i_usize((row, column), wh);
+
+                    let (kept_i, mirror_i) = match layout.kept {
+                        Triangle::Upper => (upper_i, lower_i),
+                        Triangle::Lower => (lower_i, upper_i),
+                    };
+
+                    let tiles = &mut self.tiles.tiles;
+                    if tiles[kept_i].status == Status::Unchecked {
+                        tiles[kept_i].status = tiles[mirror_i].status;
+                    }
+                    if tiles[kept_i].note.is_empty() {
+                        tiles[kept_i].note = core::mem::take(&mut tiles[mirror_i].note);
+                    }
+                    tiles[mirror_i] = tiles[kept_i].clone();
+                }
+            }
+        }
+
+        self.symmetry = symmetry;
+
+        Ok(())
+    }
+
+    /// The pairs of tiles that making the board symmetric would have to pick
+    /// between, since both are checked, but with different statuses. Each is
+    /// given as the position of the tile above the diagonal.
+    pub fn conflicting_mirrors(&self) -> impl Iterator<Item = (ColumnIndex, RowIndex)> + '_ {
+        self.upper_pairs().filter(|&(column, row)| {
+            let upper = self.status(column, row).unwrap_or_default();
+            let lower = self.status(row, column).unwrap_or_default();
+
+            upper != Status::Unchecked
+            && lower != Status::Unchecked
+            && upper != lower
+        })
+    }
+
+    fn tile_role(&self, column: ColumnIndex, row: RowIndex) -> TileRole {
+        let layout = match self.symmetry {
+            Symmetry::Asymmetric => return TileRole::Counted,
+            Symmetry::Symmetric(layout) => layout,
+        };
+
+        let is_mirror = match layout.kept {
+            Triangle::Upper => column < row,
+            Triangle::Lower => column > row,
+        };
+
+        if is_mirror {
+            TileRole::Mirror(layout.mirror_tiles)
+        } else if column == row {
+            match layout.diagonal {
+                Diagonal::Included => TileRole::Counted,
+                Diagonal::Excluded => TileRole::ExcludedDiagonal,
+                Diagonal::NotApplicable => TileRole::NotApplicableDiagonal,
+            }
+        } else {
+            TileRole::Counted
+        }
+    }
+
+    /// Whether the tile can be changed by clicking on it, or with the eye.
+    fn is_interactive(&self, column: ColumnIndex, row: RowIndex) -> bool {
+        match self.tile_role(column, row) {
+            TileRole::Counted
+            | TileRole::Mirror(MirrorTiles::Dimmed) => true,
+            TileRole::Mirror(MirrorTiles::Hidden)
+            | TileRole::ExcludedDiagonal
+            | TileRole::NotApplicableDiagonal => false,
+        }
+    }
+
+    /// Whether the tile at the given position counts towards the progress.
+    /// Tiles that mirror other tiles, and diagonal tiles that are left out,
+    /// do not.
+    pub fn is_counted(&self, column: ColumnIndex, row: RowIndex) -> bool {
+        self.tile_role(column, row) == TileRole::Counted
+    }
+
+    /// The positions of the tiles above the diagonal, whether or not the board
+    /// is currently symmetric.
+    fn upper_pairs(&self) -> impl Iterator<Item = (ColumnIndex, RowIndex)> {
+        let w = self.tiles.wh.w() as usize;
+        (0..w).flat_map(move |row| (row + 1..w).map(move |column| (column, row)))
+    }
+
+    /// The column and row index of each tile, in row-major order, except for
+    /// the tiles that are not counted.
+    fn counted_tiles(
+        &self
+    ) -> impl Iterator<Item = (ColumnIndex, RowIndex, &TileData)> + '_ {
+        self.tiles.tiles.iter()
+            .enumerate()
+            .map(|(i, tile_data)| {
+                let (column, row) = tile::i_to_xy(i, self.tiles.wh)
+                    .column_and_row();
+                (column, row, tile_data)
+            })
+            .filter(|&(column, row, _)| self.is_counted(column, row))
+    }
+
+    /// On a symmetric board, each pair of mirrored tiles counts once.
+    pub fn progress(&self) -> Progress {
+        let mut progress = Progress::default();
+
+        for (_, _, &TileData { status, .. }) in self.counted_tiles() {
+            progress.add(status);
+        }
+
+        progress
+    }
+
+    /// The progress of one column, or row. On a symmetric board, this covers
+    /// every pair with the line's label in it, so tiles that mirror other
+    /// tiles count here, while diagonal tiles that are left out still do not.
+    pub fn line_progress(&self, axis: Axis, index: usize) -> Result<Progress, OutOfBounds> {
+        let wh = self.tiles.wh;
+        let (column_count, row_count) = (wh.w() as usize, wh.h() as usize);
+
+        let pairs: Box<dyn Iterator<Item = (ColumnIndex, RowIndex)>> = match axis {
+            Axis::Columns if index < column_count => {
+                Box::new((0..row_count).map(|row| (index, row)))
+            },
+            Axis::Rows if index < row_count => {
+                Box::new((0..column_count).map(|column| (column, index)))
+            },
+            Axis::Columns | Axis::Rows => return Err(OutOfBounds),
+        };
+
+        let mut progress = Progress::default();
+
+        for (column, row) in pairs {
+            match self.tile_role(column, row) {
+                TileRole::Counted | TileRole::Mirror(_) => {
+                    progress.add(self.status(column, row)?);
+                },
+                TileRole::ExcludedDiagonal | TileRole::NotApplicableDiagonal => {},
+            }
+        }
+
+        Ok(progress)
+    }
+
+    /// The column and row index of each tile that still needs looking at,
+    /// according to `Status::is_checked`, in row-major order, along with its
+    /// status. On a symmetric board, only the tile in the kept triangle is
+    /// listed for each pair.
+    pub fn unchecked_pairs(
+        &self
+    ) -> impl Iterator<Item = (ColumnIndex, RowIndex, Status)> + '_ {
+        self.counted_tiles()
+            .filter(|(_, _, tile_data)| !tile_data.status.is_checked())
+            .map(|(column, row, tile_data)| (column, row, tile_data.status))
+    }
+
+    /// The column and row index of each tile with a note, in row-major order,
+    /// along with the note. On a symmetric board, only the tile in the kept
+    /// triangle is listed for each pair.
+    pub fn notes(
+        &self
+    ) -> impl Iterator<Item = (ColumnIndex, RowIndex, &str)> + '_ {
+        self.counted_tiles()
+            .filter(|(_, _, tile_data)| !tile_data.note.is_empty())
+            .map(|(column, row, tile_data)| (column, row, tile_data.note.as_str()))
+    }
+
+    /// Adds, removes or moves a whole column or row, along with its tiles.
+    /// On a symmetric board, the matching line on the other axis is changed
+    /// the same way, so the board stays square, and each pair keeps its
+    /// mirror.
+    pub fn edit_line(&mut self, axis: Axis, op: LineOp) -> Result<(), LineEditError> {
+        self.edit_line_steps(axis, op).map(drop)
+    }
+
+    /// Like `edit_line`, but also returns what was done, as steps that can be
+    /// undone.
+    fn edit_line_steps(
+        &mut self,
+        axis: Axis,
+        op: LineOp,
+    ) -> Result<Vec<history::LineStep>, LineEditError> {
+        let axes: &[Axis] = match self.symmetry {
+            Symmetry::Symmetric(_) => &[Axis::Columns, Axis::Rows],
+            Symmetry::Asymmetric => core::slice::from_ref(&axis),
+        };
+
+        let count = self.labels(axis).len();
+
+        let count_error = |count| LineEditError::LabelCount(LabelCountError {
+            axis,
+            count,
+        });
+
+        match op {
+            LineOp::Insert { index, .. } => {
+                if index > count {
+                    return Err(LineEditError::OutOfBounds);
+                }
+                if count >= LABEL_COUNT_MAX {
+                    return Err(count_error(count + 1));
+                }
+            },
+            LineOp::Remove { index } => {
+                if index >= count {
+                    return Err(LineEditError::OutOfBounds);
+                }
+                if count <= LABEL_COUNT_MIN {
+                    return Err(count_error(count - 1));
+                }
+            },
+            LineOp::Move { from, to } => {
+                if from >= count || to >= count {
+                    return Err(LineEditError::OutOfBounds);
+                }
+            },
+        }
+
+        let mut steps = Vec::with_capacity(axes.len());
+
+        for &axis in axes {
+            let step = match &op {
+                LineOp::Insert { index, label } => {
+                    let line = Line {
+                        axis,
+                        index: *index,
+                        label: label.clone(),
+                        tiles: vec![TileData::default(); self.line_len(axis)],
+                    };
+                    self.insert_line(line.clone());
+                    history::LineStep::Insert(line)
+                },
+                LineOp::Remove { index } => {
+                    history::LineStep::Remove(self.remove_line(axis, *index))
+                },
+                &LineOp::Move { from, to } => {
+                    if from == to {
+                        continue;
+                    }
+                    self.move_line(axis, from, to);
+                    history::LineStep::Move { axis, from, to }
+                },
+            };
+
+            steps.push(step);
+        }
+
+        Ok(steps)
+    }
+
+    /// How many tiles are in each line along the given axis.
+    fn line_len(&self, axis: Axis) -> usize {
+        match axis {
+            Axis::Columns => self.tiles.wh.h() as usize,
+            Axis::Rows => self.tiles.wh.w() as usize,
+        }
+    }
+
+    /// Changes the number of tiles along each axis, after the tiles and labels
+    /// have been changed to match.
+    fn resize_to_labels(&mut self) {
+        let wh = tile::WH::new(
+            self.column_labels.len() as tile::Count,
+            self.row_labels.len() as tile::Count,
+        ).expect("label counts should have been checked before changing them");
+
+        debug_assert_eq!(self.tiles.tiles.len(), wh.len());
+        self.tiles.wh = wh;
+
+        // Keep the eye on the board.
+        let (column, row) = self.eye.xy.column_and_row();
+        self.eye.xy = tile::XY::from_column_and_row(
+            (
+                column.min(wh.w() as usize - 1),
+                row.min(wh.h() as usize - 1),
+            ),
+            wh,
+        ).unwrap_or_default();
+    }
+
+    /// Puts `line` back in, with its label and tiles, without any checks.
+    fn insert_line(&mut self, line: Line) {
+        let w = self.tiles.wh.w() as usize;
+        let tiles = &mut self.tiles.tiles;
+
+        match line.axis {
+            Axis::Columns => {
+                // Go from the bottom up, so the indexes of the rows not yet
+                // done stay the same.
+                for (row, tile_data) in line.tiles.into_iter().enumerate().rev() {
+                    tiles.insert(row * w + line.index, tile_data);
+                }
+            },
+            Axis::Rows => {
+                let start = line.index * w;
+                tiles.splice(start..start, line.tiles);
+            },
+        }
+
+        self.labels_vec_mut(line.axis).insert(line.index, line.label);
+        self.resize_to_labels();
+    }
+
+    /// Takes out the line at `index`, with its label and tiles, without any
+    /// checks.
+    fn remove_line(&mut self, axis: Axis, index: usize) -> Line {
+        let w = self.tiles.wh.w() as usize;
+        let h = self.tiles.wh.h() as usize;
+        let tiles = &mut self.tiles.tiles;
+
+        let removed = match axis {
+            Axis::Columns => {
+                let mut removed: Vec<TileData> = (0..h).rev()
+                    .map(|row| tiles.remove(row * w + index))
+                    .collect();
+                removed.reverse();
+                removed
+            },
+            Axis::Rows => tiles.drain(index * w..(index + 1) * w).collect(),
+        };
+
+        let label = self.labels_vec_mut(axis).remove(index);
+        self.resize_to_labels();
+
+        Line {
+            axis,
+            index,
+            label,
+            tiles: removed,
+        }
+    }
+
+    /// Moves the line at `from` so it ends up at `to`, shifting the lines in
+    /// between over by one, without any checks.
+    fn move_line(&mut self, axis: Axis, from: usize, to: usize) {
+        fn shift<A>(slice: &mut [A], from: usize, to: usize, len: usize) {
+            if from < to {
+                slice[from * len..(to + 1) * len].rotate_left(len);
+            } else {
+                slice[to * len..(from + 1) * len].rotate_right(len);
+            }
+        }
+
+        let w = self.tiles.wh.w() as usize;
+
+        match axis {
+            Axis::Columns => {
+                for row in self.tiles.tiles.chunks_mut(w) {
+                    shift(row, from, to, 1);
+                }
+            },
+            Axis::Rows => shift(&mut self.tiles.tiles, from, to, w),
+        }
+
+        shift(self.labels_vec_mut(axis), from, to, 1);
+    }
+
+    fn labels_vec_mut(&mut self, axis: Axis) -> &mut Vec<Label> {
+        match axis {
+            Axis::Columns => &mut self.column_labels,
+            Axis::Rows => &mut self.row_labels,
+        }
+    }
+}
+
+/// Something to do to a whole column or row. See `Board::edit_line`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum LineOp {
+    /// Adds a line with every tile unchecked, so it ends up at `index`. An
+    /// `index` equal to the number of lines adds it at the end.
+    Insert { index: usize, label: Label },
+    Remove { index: usize },
+    /// Moves the line at `from` to `to`, shifting the lines in between over
+    /// by one to make room.
+    Move { from: usize, to: usize },
+}
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum LineEditError {
+    OutOfBounds,
+    /// The board would end up with too many, or too few lines.
+    LabelCount(LabelCountError),
+}
+
+impl core::fmt::Display for LineEditError {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::OutOfBounds => write!(f, "there is no line at that index"),
+            Self::LabelCount(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for LineEditError {}
+
+/// A column or row, along with its label and tiles, from the top or the left.
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct Line {
+    axis: Axis,
+    index: usize,
+    label: Label,
+    tiles: Vec<TileData>,
+}
+
+#[test]
+fn boards_have_between_3_and_200_lines_on_each_axis() {
+    let labels = |count| vec![Label::default(); count];
+
+    assert!(Board::with_labels(labels(3)).is_ok());
+    assert!(Board::with_axis_labels(labels(200), labels(3)).is_ok());
+
+    let error = Board::with_axis_labels(labels(3), labels(2)).unwrap_err();
+    assert_eq!(error, LabelCountError { axis: Axis::Rows, count: 2 });
+    assert_eq!(
+        error.to_string(),
+        "a board needs between 3 and 200 row labels, not 2"
+    );
+    assert!(Board::with_labels(labels(201)).is_err());
+}
+
+#[test]
+fn symmetric_boards_count_each_pair_once() {
+    let mut board = Board::with_labels(
+        ["a", "b", "c"].map(str::to_owned).to_vec()
+    ).unwrap();
+
+    board.set_symmetry(Symmetry::Symmetric(<_>::default())).unwrap();
+    board.set_status(2, 0, Status::Passes).unwrap();
+
+    assert_eq!(board.status(0, 2), Ok(Status::Passes));
+
+    let progress = board.progress();
+    assert_eq!((progress.checked, progress.total), (1, 6));
+    assert_eq!(
+        board.unchecked_pairs().map(|(c, r, _)| (c, r)).collect::<Vec<_>>(),
+        [(0, 0), (0, 1), (1, 1), (1, 2), (2, 2)]
+    );
+}
+
+#[test]
+fn making_a_board_symmetric_keeps_the_checks_from_either_half() {
+    let mut board = Board::with_labels(
+        ["a", "b", "c"].map(str::to_owned).to_vec()
+    ).unwrap();
+
+    board.set_status(1, 0, Status::Fails).unwrap();
+    board.set_symmetry(Symmetry::Symmetric(<_>::default())).unwrap();
+
+    assert_eq!(board.status(0, 1), Ok(Status::Fails));
+
+    let mut conflicting = Board::with_labels(vec![Label::default(); 3]).unwrap();
+    conflicting.set_status(2, 0, Status::Passes).unwrap();
+    conflicting.set_status(0, 2, Status::Fails).unwrap();
+    conflicting.set_status(1, 2, Status::Blocked).unwrap();
+    assert_eq!(conflicting.conflicting_mirrors().collect::<Vec<_>>(), [(2, 0)]);
+
+    let mut not_square = Board::with_axis_labels(
+        vec![Label::default(); 3],
+        vec![Label::default(); 4],
+    ).unwrap();
+    assert_eq!(
+        not_square.set_symmetry(Symmetry::Symmetric(<_>::default())),
+        Err(NotSquare)
+    );
+}
+
+#[test]
+fn the_kept_triangle_and_diagonal_decide_which_tiles_are_counted() {
+    let mut board = Board::with_labels(
+        ["a", "b", "c"].map(str::to_owned).to_vec()
+    ).unwrap();
+
+    board.set_symmetry(Symmetry::Symmetric(SymmetricLayout {
+        kept: Triangle::Upper,
+        mirror_tiles: MirrorTiles::Hidden,
+        diagonal: Diagonal::Excluded,
+    })).unwrap();
+
+    assert_eq!(board.progress().total, 3);
+    assert_eq!(
+        board.unchecked_pairs().map(|(c, r, _)| (c, r)).collect::<Vec<_>>(),
+        [(1, 0), (2, 0), (2, 1)]
+    );
+
+    board.set_symmetry(Symmetry::Symmetric(SymmetricLayout {
+        kept: Triangle::Lower,
+        mirror_tiles: MirrorTiles::Dimmed,
+        diagonal: Diagonal::NotApplicable,
+    })).unwrap();
+
+    assert_eq!(
+        board.unchecked_pairs().map(|(c, r, _)| (c, r)).collect::<Vec<_>>(),
+        [(0, 1), (0, 2), (1, 2)]
+    );
+}
+
+#[test]
+fn progress_counts_each_status_but_only_findings_as_checked() {
+    let mut board = Board::with_labels(vec![Label::default(); 3]).unwrap();
+
+    for i in 0..3 {
+        board.set_status(2, i, Status::Passes).unwrap();
+        board.set_status(i, 2, Status::Passes).unwrap();
+    }
+    board.set_status(0, 0, Status::Passes).unwrap();
+    board.set_status(1, 0, Status::Fails).unwrap();
+    board.set_status(0, 1, Status::Blocked).unwrap();
+
+    let progress = board.progress();
+
+    assert_eq!((progress.checked, progress.total), (7, 9));
+    assert_eq!(progress.counts.get(Status::Unchecked), 1);
+    assert_eq!(progress.counts.get(Status::Blocked), 1);
+    assert_eq!(progress.counts.iter().map(|(_, n)| n).sum::<usize>(), 9);
+    assert_eq!(
+        board.unchecked_pairs().collect::<Vec<_>>(),
+        [(0, 1, Status::Blocked), (1, 1, Status::Unchecked)]
+    );
+}
+
+#[test]
+fn line_progress_covers_every_pair_with_the_line_in_it() {
+    let labels = ["a", "b", "c"].map(str::to_owned).to_vec();
+    let mut board = Board::with_labels(labels).unwrap();
+    board.set_symmetry(Symmetry::Symmetric(SymmetricLayout {
+        diagonal: Diagonal::Excluded,
+        ..<_>::default()
+    })).unwrap();
+    board.set_status(0, 1, Status::Passes).unwrap();
+    board.set_status(2, 1, Status::Blocked).unwrap();
+
+    let row_b = board.line_progress(Axis::Rows, 1).unwrap();
+    assert_eq!((row_b.checked, row_b.total), (1, 2));
+    assert_eq!(row_b.percent(), 50);
+    // The same pairs, from the other direction.
+    assert_eq!(board.line_progress(Axis::Columns, 1), Ok(row_b));
+
+    let column_c = board.line_progress(Axis::Columns, 2).unwrap();
+    assert_eq!((column_c.checked, column_c.total), (0, 2));
+    assert_eq!(column_c.counts.get(Status::Blocked), 1);
+
+    assert_eq!(board.line_progress(Axis::Rows, 3), Err(OutOfBounds));
+}
+
+#[test]
+fn editing_lines_carries_their_tiles_and_labels_along() {
+    let mut board = Board::with_axis_labels(
+        ["a", "b", "c"].map(str::to_owned).to_vec(),
+        ["x", "y", "z"].map(str::to_owned).to_vec(),
+    ).unwrap();
+    board.set_status(0, 1, Status::Passes).unwrap();
+    board.set_status(2, 0, Status::Fails).unwrap();
+
+    board.edit_line(Axis::Columns, LineOp::Move { from: 0, to: 2 }).unwrap();
+    assert_eq!(board.labels(Axis::Columns), ["b", "c", "a"]);
+    assert_eq!(board.status(2, 1), Ok(Status::Passes));
+    assert_eq!(board.status(1, 0), Ok(Status::Fails));
+
+    board.edit_line(Axis::Rows, LineOp::Insert {
+        index: 1,
+        label: "new".to_owned(),
+    }).unwrap();
+    assert_eq!(board.labels(Axis::Rows), ["x", "new", "y", "z"]);
+    assert_eq!(board.status(2, 1), Ok(Status::Unchecked));
+    assert_eq!(board.status(2, 2), Ok(Status::Passes));
+
+    board.edit_line(Axis::Columns, LineOp::Insert {
+        index: 3,
+        label: "d".to_owned(),
+    }).unwrap();
+    board.edit_line(Axis::Columns, LineOp::Remove { index: 1 }).unwrap();
+    assert_eq!(board.labels(Axis::Columns), ["b", "a", "d"]);
+    assert_eq!(board.status(1, 2), Ok(Status::Passes));
+    assert_eq!(board.progress().counts.get(Status::Fails), 0);
+
+    assert_eq!(
+        board.edit_line(Axis::Rows, LineOp::Remove { index: 4 }),
+        Err(LineEditError::OutOfBounds)
+    );
+}
+
+#[test]
+fn editing_a_line_on_a_symmetric_board_edits_its_mirror_too() {
+    let mut board = Board::with_labels(
+        ["a", "b", "c", "d"].map(str::to_owned).to_vec()
+    ).unwrap();
+    board.set_symmetry(Symmetry::Symmetric(<_>::default())).unwrap();
+    board.set_status(2, 0, Status::Blocked).unwrap();
+
+    board.edit_line(Axis::Rows, LineOp::Move { from: 2, to: 0 }).unwrap();
+    assert_eq!(board.labels(Axis::Columns), ["c", "a", "b", "d"]);
+    assert_eq!(board.labels(Axis::Rows), ["c", "a", "b", "d"]);
+    assert_eq!(board.status(0, 1), Ok(Status::Blocked));
+    assert_eq!(board.status(1, 0), Ok(Status::Blocked));
+
+    board.edit_line(Axis::Columns, LineOp::Remove { index: 2 }).unwrap();
+    assert_eq!(board.labels(Axis::Rows), ["c", "a", "d"]);
+    assert_eq!(board.progress().total, 6);
+
+    let mut smallest = Board::with_labels(vec![Label::default(); LABEL_COUNT_MIN]).unwrap();
+    assert_eq!(
+        smallest.edit_line(Axis::Columns, LineOp::Remove { index: 0 }),
+        Err(LineEditError::LabelCount(LabelCountError {
+            axis: Axis::Columns,
+            count: LABEL_COUNT_MIN - 1,
+        }))
+    );
+}
+
+#[test]
+fn label_lists_can_skip_comments_and_blank_lines() {
+    let text = "# components\n  parser \n\n\t# not yet\nlexer\r\n";
+
+    assert_eq!(
+        parse_label_list(text, LabelListOptions::default()),
+        ["# components", "  parser ", "", "\t# not yet", "lexer"]
+    );
+    assert_eq!(
+        parse_label_list(text, LabelListOptions {
+            trim: true,
+            skip_comments: true,
+            skip_blank: true,
+        }),
+        ["parser", "lexer"]
+    );
+}
+
+#[test]
+fn filling_labels_returns_the_ones_that_did_not_fit() {
+    let mut board = Board::with_labels(vec![Label::default(); 3]).unwrap();
+    let labels = ["a", "b", "c", "d"].map(str::to_owned);
+
+    assert_eq!(board.fill_labels(Axis::Rows, &labels), ["d"]);
+    assert_eq!(board.labels(Axis::Rows), ["a", "b", "c"]);
+    assert_eq!(board.fill_labels(Axis::Columns, &labels[..1]), [] as [Label; 0]);
+    assert_eq!(board.labels(Axis::Columns), ["a", "", ""]);
+}
+
+#[test]
+fn syncing_labels_keeps_the_tiles_of_lines_that_stay() {
+    let mut board = Board::with_axis_labels(
+        ["a", "b", "c"].map(str::to_owned).to_vec(),
+        ["x", "y", "z"].map(str::to_owned).to_vec(),
+    ).unwrap();
+    board.set_status(0, 1, Status::Passes).unwrap();
+    board.set_status(2, 0, Status::Fails).unwrap();
+
+    let labels = ["c", "d", "a"].map(str::to_owned);
+    board.sync_labels(Axis::Columns, &labels).unwrap();
+
+    assert_eq!(board.labels(Axis::Columns), labels);
+    assert_eq!(board.status(0, 0), Ok(Status::Fails));
+    assert_eq!(board.status(1, 0), Ok(Status::Unchecked));
+    assert_eq!(board.status(2, 1), Ok(Status::Passes));
+
+    // Nothing in common.
+    let labels = ["p", "q", "r"].map(str::to_owned);
+    board.sync_labels(Axis::Rows, &labels).unwrap();
+    assert_eq!(board.labels(Axis::Rows), labels);
+    assert_eq!(board.progress().checked, 0);
+}
+
+#[test]
+fn syncing_labels_works_at_the_most_lines() {
+    let mut board = Board::with_labels(
+        (0..LABEL_COUNT_MAX).map(|i| format!("old {i}")).collect()
+    ).unwrap();
+    board.set_symmetry(Symmetry::Symmetric(<_>::default())).unwrap();
+
+    let labels: Vec<Label> = (0..LABEL_COUNT_MAX).map(|i| format!("new {i}")).collect();
+    board.sync_labels(Axis::Rows, &labels).unwrap();
+
+    assert_eq!(board.labels(Axis::Rows), labels);
+    assert_eq!(board.labels(Axis::Columns), labels);
+}
+
+impl State {
+    pub fn board(&self) -> &Board {
+        &self.board
+    }
+
+    /// Changes made through this are not recorded in the history. See
+    /// `edit_board` for that.
+    pub fn board_mut(&mut self) -> &mut Board {
+        &mut self.board
+    }
+
+    pub fn eye_movement(&self) -> EyeMovement {
+        self.ui.eye_movement
+    }
+
+    pub fn set_eye_movement(&mut self, eye_movement: EyeMovement) {
+        self.ui.eye_movement = eye_movement;
+    }
+
+    pub fn history(&self) -> &History {
+        &self.history
+    }
+
+    /// Whether typing is going into a label or a note, in which case keys like
+    /// Escape should not do anything else.
+    pub fn is_editing_text(&self) -> bool {
+        self.ui.focused_label.is_some()
+        || matches!(self.ui.mode, UiMode::EditNote(_))
+    }
+
+    /// Focuses the text box for the given label, so typing goes into it.
+    fn focus_label(&mut self, axis: Axis, index: usize, select_all: bool) {
+        let original = self.board.labels(axis)[index].clone();
+        let cursor = if select_all {
+            text::Cursor { caret: original.len(), anchor: 0 }
+        } else {
+            text::Cursor::at(original.len())
+        };
+
+        // Typing after focusing a label, even the same one again, is a new
+        // step.
+        self.history.break_coalescing();
+
+        self.ui.focused_label = Some(FocusedLabel {
+            axis,
+            index,
+            original,
+            cursor,
+            dragging: false,
+            idle_frames: 0,
+        });
+    }
+
+    /// Keeps any changes made to the focused label, and stops typing from
+    /// going into it.
+    fn unfocus_label(&mut self) {
+        if self.ui.focused_label.take().is_some() {
+            self.history.break_coalescing();
+        }
+    }
+
+    /// Returns the text that was copied or cut since the last call, which the
+    /// platform layer should put on the clipboard.
+    pub fn take_copied_text(&mut self) -> Option<String> {
+        self.ui.copied_text.take()
+    }
+
+    /// Runs `edit` on the board, and records whatever it changed as one step
+    /// that can be undone. Prefer `edit_line` for adding, removing or moving
+    /// lines, since changes like that made through here are recorded as a
+    /// copy of the whole board.
+    pub fn edit_board<R>(&mut self, edit: impl FnOnce(&mut Board) -> R) -> R {
+        self.edit_board_coalescing(None, edit)
+    }
+
+    fn edit_board_coalescing<R>(
+        &mut self,
+        coalesce: Option<history::Coalesce>,
+        edit: impl FnOnce(&mut Board) -> R,
+    ) -> R {
+        let before = history::Snapshot::of(&self.board);
+        let wh = self.board.tiles.wh;
+        let output = edit(&mut self.board);
+        self.history.record(before, &self.board, coalesce);
+        if self.board.tiles.wh != wh {
+            self.forget_tiles();
+        }
+
+        output
+    }
+
+    /// Adds, removes or moves a whole column or row, along with its tiles, as
+    /// one step that can be undone. See `Board::edit_line`.
+    pub fn edit_line(&mut self, axis: Axis, op: LineOp) -> Result<(), LineEditError> {
+        let steps = self.board.edit_line_steps(axis, op)?;
+        self.history.record_lines(steps);
+        self.forget_tiles();
+
+        Ok(())
+    }
+
+    /// Drops the parts of the UI that point at particular tiles or labels,
+    /// after lines were added, removed or moved, since they may now point at
+    /// different ones, or none at all.
+    fn forget_tiles(&mut self) {
+        self.ui.selection = None;
+        self.ui.drag = None;
+        self.unfocus_label();
+        if let UiMode::EditNote(_) = self.ui.mode {
+            self.ui.mode = UiMode::Checking;
+        }
+    }
+
+    /// The columns and rows of the selected rectangle of tiles, if any.
+    pub fn selection(&self) -> Option<(RangeInclusive<ColumnIndex>, RangeInclusive<RowIndex>)> {
+        self.ui.selection.map(Selection::ranges)
+    }
+
+    pub fn clear_selection(&mut self) {
+        self.ui.selection = None;
+    }
+
+    /// Selects every tile in the given column, or row. With `extend`, the
+    /// selection instead grows to cover all the lines between the line it
+    /// started from and the given one.
+    pub fn select_line(
+        &mut self,
+        axis: Axis,
+        index: usize,
+        extend: bool,
+    ) -> Result<(), OutOfBounds> {
+        let wh = self.board.tiles.wh;
+        let line = Selection::line(axis, index, wh).ok_or(OutOfBounds)?;
+
+        self.ui.selection = match (self.ui.selection, extend) {
+            (Some(selection), true) => {
+                let (anchor_column, anchor_row) = selection.anchor.column_and_row();
+                let anchor_index = match axis {
+                    Axis::Columns => anchor_column,
+                    Axis::Rows => anchor_row,
+                };
+                let start = Selection::line(axis, anchor_index, wh).ok_or(OutOfBounds)?;
+
+                Some(Selection {
+                    anchor: start.anchor,
+                    corner: line.corner,
+                })
+            },
+            (Some(_), false) | (None, _) => Some(line),
+        };
+
+        Ok(())
+    }
+
+    /// Sets every selected tile to the status after the one the first
+    /// selected tile that can be clicked on has, as one step that can be
+    /// undone. Returns whether anything was set.
+    pub fn cycle_selected_statuses(&mut self) -> bool {
+        let Some((columns, rows)) = self.selection() else {
+            return false;
+        };
+
+        let first_status = rows
+            .flat_map(|row| columns.clone().map(move |column| (column, row)))
+            .find(|&(column, row)| self.board.is_interactive(column, row))
+            .and_then(|(column, row)| self.board.status(column, row).ok());
+
+        match first_status {
+            Some(status) => self.set_selected_statuses(status.next()),
+            None => false,
+        }
+    }
+
+    /// Sets the status of every selected tile that can be clicked on, as one
+    /// step that can be undone. Returns whether there was a selection.
+    pub fn set_selected_statuses(&mut self, status: Status) -> bool {
+        let Some((columns, rows)) = self.selection() else {
+            return false;
+        };
+
+        self.edit_board(|board| {
+            for row in rows {
+                for column in columns.clone() {
+                    if board.is_interactive(column, row) {
+                        // This also sets the mirrored tile, if any.
+                        let _ = board.set_status(column, row, status);
+                    }
+                }
+            }
+        });
+
+        true
+    }
+
+    /// Returns whether there was anything to undo.
+    pub fn undo(&mut self) -> bool {
+        let wh = self.board.tiles.wh;
+        let undone = self.history.undo(&mut self.board);
+        if self.board.tiles.wh != wh {
+            self.forget_tiles();
+        }
+
+        undone
+    }
+
+    /// Returns whether there was anything to redo.
+    pub fn redo(&mut self) -> bool {
+        let wh = self.board.tiles.wh;
+        let redone = self.history.redo(&mut self.board);
+        if self.board.tiles.wh != wh {
+            self.forget_tiles();
+        }
+
+        redone
+    }
+}
+
+impl From<Board> for State {
+    fn from(board: Board) -> Self {
+        Self {
+            board,
+            ..<_>::default()
+        }
+    }
+}
+
+#[test]
+fn setting_the_selection_skips_tiles_that_cannot_be_clicked() {
+    let labels = ["a", "b", "c"].map(str::to_owned).to_vec();
+    let mut state = State::from(Board::with_labels(labels).unwrap());
+    state.board.set_symmetry(Symmetry::Symmetric(SymmetricLayout {
+        diagonal: Diagonal::Excluded,
+        ..<_>::default()
+    })).unwrap();
+
+    let txy = |x, y| {
+        let wh = state.board.tiles.wh;
+        tile::XY {
+            x: tile::X::try_from_coord(x, wh).unwrap(),
+            y: tile::Y::try_from_coord(y, wh).unwrap(),
+        }
+    };
+    state.ui.selection = Some(Selection { anchor: txy(1, 2), corner: txy(0, 1) });
+
+    assert_eq!(state.selection(), Some((0..=1, 1..=2)));
+    assert!(state.set_selected_statuses(Status::Fails));
+
+    assert_eq!(state.board.status(0, 1), Ok(Status::Fails));
+    assert_eq!(state.board.status(0, 2), Ok(Status::Fails));
+    assert_eq!(state.board.status(1, 2), Ok(Status::Fails));
+    // Mirrored onto the tile that was left out.
+    assert_eq!(state.board.status(1, 0), Ok(Status::Fails));
+    // On the excluded diagonal.
+    assert_eq!(state.board.status(1, 1), Ok(Status::Unchecked));
+
+    assert!(state.undo());
+    assert_eq!(state.board.progress().checked, 0);
+}
+
+#[test]
+fn clicking_a_selected_line_again_cycles_it_from_its_first_tile() {
+    let labels = ["a", "b", "c"].map(str::to_owned).to_vec();
+    let mut state = State::from(Board::with_labels(labels).unwrap());
+    state.board.set_status(1, 2, Status::Blocked).unwrap();
+
+    state.select_line(Axis::Rows, 2, false).unwrap();
+    assert_eq!(state.selection(), Some((0..=2, 2..=2)));
+    assert!(state.cycle_selected_statuses());
+    assert_eq!(
+        (0..3).map(|column| state.board.status(column, 2).unwrap()).collect::<Vec<_>>(),
+        [Status::Passes; 3]
+    );
+
+    state.select_line(Axis::Columns, 2, false).unwrap();
+    state.select_line(Axis::Columns, 0, true).unwrap();
+    assert_eq!(state.selection(), Some((0..=2, 0..=2)));
+
+    assert_eq!(state.select_line(Axis::Columns, 3, false), Err(OutOfBounds));
+}
+
+#[cfg(test)]
+impl<A> ClearableStorage<A> for Vec<A> {
+    fn clear(&mut self) {
+        Vec::clear(self);
+    }
+
+    fn push(&mut self, a: A) {
+        Vec::push(self, a);
+    }
+}
+
+#[test]
+fn dragging_paints_the_tiles_skipped_over_between_frames() {
+    let mut state = State::from(Board::with_labels(vec![Label::default(); 8]).unwrap());
+    let mut commands = Vec::new();
+
+    let mut frame = |state: &mut State, input_flags, (column, row): (usize, usize)| {
+        let sizes = &state.ui.sizes;
+        let cursor_xy = CursorXY {
+            x: sizes.board_xywh.x + (column as DrawX + 0.5) * sizes.tile_side_length,
+            y: sizes.board_xywh.y + (row as DrawY + 0.5) * sizes.tile_side_length,
+        };
+
+        update(
+            state,
+            &mut commands,
+            input_flags,
+            <_>::default(),
+            &|text, _| text.len() as DrawLength * 8.,
+            cursor_xy,
+            DrawWH { w: 1920., h: 1080. },
+        );
+    };
+
+    // Lays out the board, so the tiles can be found.
+    frame(&mut state, 0, (0, 0));
+    frame(&mut state, INPUT_LEFT_MOUSE_CHANGED | INPUT_LEFT_MOUSE_DOWN, (0, 0));
+    frame(&mut state, INPUT_LEFT_MOUSE_DOWN, (5, 3));
+    frame(&mut state, INPUT_LEFT_MOUSE_CHANGED, (5, 3));
+
+    for (column, row) in [(0, 0), (1, 1), (2, 1), (3, 2), (4, 2), (5, 3)] {
+        assert_eq!(state.board.status(column, row), Ok(Status::Passes), "{column}, {row}");
+    }
+    assert_eq!(state.board.progress().checked, 6);
+
+    // The whole drag is one step.
+    assert!(state.undo());
+    assert_eq!(state.board.progress().checked, 0);
+}
+
+#[test]
+fn notes_keep_new_lines_and_can_be_copied_and_cut() {
+    let labels = ["a", "b", "c"].map(str::to_owned).to_vec();
+    let mut state = State::from(Board::with_labels(labels).unwrap());
+    let mut commands = Vec::new();
+
+    let mut frame = |state: &mut State, input_flags, text_input: TextInput| {
+        update(
+            state,
+            &mut commands,
+            input_flags,
+            text_input,
+            &|text, _| text.len() as DrawLength * 8.,
+            CursorXY::default(),
+            DrawWH { w: 1920., h: 1080. },
+        );
+    };
+    let keys = |keys| TextInput { keys, ..<_>::default() };
+    let typed = |c| {
+        let mut input = TextInput::default();
+        input.chars[0] = c;
+        input
+    };
+
+    frame(&mut state, INPUT_NOTE_PRESSED, <_>::default());
+    frame(&mut state, 0, typed('a'));
+    frame(&mut state, 0, keys(TEXT_ENTER));
+    frame(&mut state, 0, TextInput {
+        pasted: Some("b\r\nc".to_owned()),
+        ..<_>::default()
+    });
+    assert!(matches!(state.ui.mode, UiMode::EditNote(_)));
+    assert_eq!(state.board.note(0, 0), Ok("a\nb\nc"));
+
+    // The selection is kept between frames, as the keys usually are.
+    frame(&mut state, 0, keys(TEXT_CTRL_DOWN | TEXT_SELECT_ALL));
+    frame(&mut state, 0, keys(TEXT_CTRL_DOWN | TEXT_COPY));
+    assert_eq!(state.take_copied_text().as_deref(), Some("a\nb\nc"));
+    assert_eq!(state.board.note(0, 0), Ok("a\nb\nc"));
+
+    frame(&mut state, 0, keys(TEXT_CTRL_DOWN | TEXT_CUT));
+    assert_eq!(state.take_copied_text().as_deref(), Some("a\nb\nc"));
+    assert_eq!(state.board.note(0, 0), Ok(""));
+}
+
+pub fn sizes(state: &State) -> draw::Sizes {
+    state.ui.sizes.clone()
+}
+
+pub type InputFlags = u32;
+
+pub const INPUT_UP_PRESSED: InputFlags              = 0b0000_0000_0000_0000_0000_0000_0000_0001;
+pub const INPUT_DOWN_PRESSED: InputFlags            = 0b0000_0000_0000_0000_0000_0000_0000_0010;
+pub const INPUT_LEFT_PRESSED: InputFlags            = 0b0000_0000_0000_0000_0000_0000_0000_0100;
+pub const INPUT_RIGHT_PRESSED: InputFlags           = 0b0000_0000_0000_0000_0000_0000_0000_1000;
+
+pub const INPUT_UP_DOWN: InputFlags                 = 0b0000_0000_0000_0000_0000_0000_0001_0000;
+pub const INPUT_DOWN_DOWN: InputFlags               = 0b0000_0000_0000_0000_0000_0000_0010_0000;
+pub const INPUT_LEFT_DOWN: InputFlags               = 0b0000_0000_0000_0000_0000_0000_0100_0000;
+pub const INPUT_RIGHT_DOWN: InputFlags              = 0b0000_0000_0000_0000_0000_0000_1000_0000;
+
+pub const INPUT_INTERACT_PRESSED: InputFlags        = 0b0000_0000_0000_0000_0000_0001_0000_0000;
+pub const INPUT_INTERACT_DOWN: InputFlags           = 0b0000_0000_0000_0000_0000_0010_0000_0000;
+
+/// Should be set if the mouse button was pressed or released this frame.
+pub const INPUT_LEFT_MOUSE_CHANGED: InputFlags      = 0b0000_0000_0000_0000_0000_0100_0000_0000;
+pub const INPUT_LEFT_MOUSE_DOWN: InputFlags         = 0b0000_0000_0000_0000_0000_1000_0000_0000;
+
+/// Cycles between an asymmetric board, and a symmetric one with the mirror
+/// tiles dimmed or hidden. Does nothing on boards that are not square.
+pub const INPUT_SYMMETRY_PRESSED: InputFlags        = 0b0000_0000_0000_0000_0001_0000_0000_0000;
+/// Swaps which triangle of a symmetric board is kept.
+pub const INPUT_TRIANGLE_PRESSED: InputFlags        = 0b0000_0000_0000_0000_0010_0000_0000_0000;
+/// Cycles through the ways the diagonal of a symmetric board can be handled.
+pub const INPUT_DIAGONAL_PRESSED: InputFlags        = 0b0000_0000_0000_0000_0100_0000_0000_0000;
+/// Should be set on the frame the right mouse button was pressed. Moves the
+/// tile under the cursor back one step in the status cycle.
+pub const INPUT_RIGHT_MOUSE_PRESSED: InputFlags     = 0b0000_0000_0000_0000_1000_0000_0000_0000;
+/// Starts editing the note of the tile under the cursor, or the tile the eye
+/// is on, if the cursor is not over a tile.
+pub const INPUT_NOTE_PRESSED: InputFlags            = 0b0000_0000_0000_0001_0000_0000_0000_0000;
+
+/// Undoes the last change to the board. See `State::undo`.
+pub const INPUT_UNDO_PRESSED: InputFlags            = 0b0000_0000_0000_0010_0000_0000_0000_0000;
+/// Redoes the last undone change to the board. See `State::redo`.
+pub const INPUT_REDO_PRESSED: InputFlags            = 0b0000_0000_0000_0100_0000_0000_0000_0000;
+/// Held down while pressing on a tile to select a rectangle of tiles instead
+/// of painting, or while clicking on a label to select all the lines between
+/// it and the selected line.
+pub const INPUT_SHIFT_DOWN: InputFlags              = 0b0000_0000_0000_1000_0000_0000_0000_0000;
+/// Sets every selected tile to passes.
+pub const INPUT_CHECK_ALL_PRESSED: InputFlags       = 0b0000_0000_0001_0000_0000_0000_0000_0000;
+/// Sets every selected tile to unchecked.
+pub const INPUT_CLEAR_ALL_PRESSED: InputFlags       = 0b0000_0000_0010_0000_0000_0000_0000_0000;
+
+const TEXT_BOX_W_TILES: u8 = 15;
+
+/// How many label text boxes are shown at once. This many fit in the center of
+/// the screen.
+const LABELS_PER_PAGE: usize = draw::CENTER_UI_WIDTH_TILES;
+
+/// `i` is the index of the text box on the current page.
+fn text_box_rect(sizes: &Sizes, i: usize) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let center_xy = center_ui_xy(sizes);
+    let x = center_xy.x;
+    let y = center_xy.y + i as DrawLength * tile_side_length;
+
+    let number_w = tile_side_length;
+
+    let label_x = x + number_w;
+
+    draw::Rect {
+        min_x: label_x,
+        min_y: y,
+        max_x: label_x
+               + TEXT_BOX_W_TILES as DrawLength * tile_side_length,
+        max_y: y + tile_side_length,
+    }
+}
+
+/// `dir` should be `Up` for the previous page, or `Down` for the next one.
+fn label_page_button_rect(sizes: &Sizes, dir: Dir) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let center_xy = center_ui_xy(sizes);
+    let x = center_xy.x
+        + (1 + TEXT_BOX_W_TILES) as DrawLength * tile_side_length;
+    let y = center_xy.y + if let Dir::Up = dir {
+        0.
+    } else {
+        (LABELS_PER_PAGE - 1) as DrawLength * tile_side_length
+    };
+
+    draw::Rect {
+        min_x: x,
+        min_y: y,
+        max_x: x + tile_side_length,
+        max_y: y + tile_side_length,
+    }
+}
+
+/// The buttons that change the focused label's line go at the top of the area
+/// to the right of the board, with a gap between each.
+fn line_button_rect(sizes: &Sizes, button: LineButton) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let right_xy = draw::right_ui_xy(sizes);
+    let i = LineButton::ALL.iter()
+        .position(|&b| b == button)
+        .unwrap_or_default();
+    let y = right_xy.y + (i * 2) as DrawLength * tile_side_length;
+
+    draw::Rect {
+        min_x: right_xy.x,
+        min_y: y,
+        max_x: right_xy.x
+            + EDIT_LABELS_BUTTON_W_TILES as DrawLength * tile_side_length,
+        max_y: y + tile_side_length,
+    }
+}
+
+/// How many UI tiles wide the buttons that start editing the labels are.
+const EDIT_LABELS_BUTTON_W_TILES: usize = draw::RIGHT_UI_WIDTH_TILES;
+
+/// The buttons that start editing the labels go at the bottom of the area to
+/// the right of the board, the column one above the row one.
+fn edit_labels_button_rect(sizes: &Sizes, axis: Axis) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let right_xy = draw::right_ui_xy(sizes);
+    let rows_from_bottom = match axis {
+        Axis::Columns => 3,
+        Axis::Rows => 1,
+    };
+    let y = right_xy.y
+        + (draw::CENTER_UI_WIDTH_TILES - rows_from_bottom) as DrawLength
+            * tile_side_length;
+
+    draw::Rect {
+        min_x: right_xy.x,
+        min_y: y,
+        max_x: right_xy.x
+            + EDIT_LABELS_BUTTON_W_TILES as DrawLength * tile_side_length,
+        max_y: y + tile_side_length,
+    }
+}
+
+/// How many UI tiles tall the note editing box is.
+const NOTE_BOX_H_TILES: usize = 8;
+
+/// The box the note being edited is shown in, below a line for the labels of
+/// the pair the note is for.
+fn note_box_rect(sizes: &Sizes) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let center_xy = center_ui_xy(sizes);
+    let y = center_xy.y + tile_side_length;
+
+    draw::Rect {
+        min_x: center_xy.x,
+        min_y: y,
+        max_x: center_xy.x
+            + (1 + TEXT_BOX_W_TILES) as DrawLength * tile_side_length,
+        max_y: y + NOTE_BOX_H_TILES as DrawLength * tile_side_length,
+    }
+}
+
+/// How many UI tiles wide the text on each status line of the progress panel
+/// is. The bar for the line takes up the rest.
+const STATS_TEXT_W_TILES: usize = 5;
+
+/// The progress panel goes in the area to the left of the board. It is cut
+/// short on the right for boards with row labels wide enough to reach into
+/// that area.
+fn stats_panel_rect(sizes: &Sizes) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let left_xy = draw::left_ui_xy(sizes);
+    let labels_x = left_label_rect(sizes).min_x;
+    let w_tiles = (((labels_x - left_xy.x) / tile_side_length).floor() as usize)
+        .min(draw::LEFT_UI_WIDTH_TILES);
+
+    draw::Rect {
+        min_x: left_xy.x,
+        min_y: left_xy.y,
+        max_x: left_xy.x + w_tiles as DrawLength * tile_side_length,
+        max_y: left_xy.y
+            + draw::CENTER_UI_WIDTH_TILES as DrawLength * tile_side_length,
+    }
+}
+
+/// How many UI tiles tall the box describing the hovered tile is.
+const TOOLTIP_H_TILES: usize = 4;
+
+/// Where the labels and status of the hovered tile are shown, at the top of
+/// the area to the right of the board.
+fn tooltip_rect(sizes: &Sizes) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let right_xy = draw::right_ui_xy(sizes);
+
+    draw::Rect {
+        min_x: right_xy.x,
+        min_y: right_xy.y,
+        max_x: right_xy.x
+            + draw::RIGHT_UI_WIDTH_TILES as DrawLength * tile_side_length,
+        max_y: right_xy.y + TOOLTIP_H_TILES as DrawLength * tile_side_length,
+    }
+}
+
+/// How many UI tiles tall the note shown when hovering over a tile is.
+const NOTE_PREVIEW_H_TILES: usize = 6;
+
+/// Where the note of the hovered tile is shown, below the tooltip and a line
+/// for a title.
+fn note_preview_rect(sizes: &Sizes) -> draw::Rect {
+    let tile_side_length = sizes.ui_tile_side_length;
+
+    let right_xy = draw::right_ui_xy(sizes);
+    let y = right_xy.y + (TOOLTIP_H_TILES + 2) as DrawLength * tile_side_length;
+
+    draw::Rect {
+        min_x: right_xy.x,
+        min_y: y,
+        max_x: right_xy.x
+            + draw::RIGHT_UI_WIDTH_TILES as DrawLength * tile_side_length,
+        max_y: y + NOTE_PREVIEW_H_TILES as DrawLength * tile_side_length,
+    }
+}
+
+/// Pushes the sprites for a box with a border around it, covering `rect`. The
+/// sides of `rect` should be whole multiples of the UI tile side length, and
+/// at least two tiles long.
+fn push_panel(
+    commands: &mut dyn ClearableStorage<draw::Command>,
+    sizes: &Sizes,
+    rect: &draw::Rect,
+    border_kind: BorderKind,
+) {
+    let tile_side_length = sizes.ui_tile_side_length;
+    let ui_tile_draw_wh = ui_tile_draw_wh(sizes);
+
+    let w_tiles = ((rect.max_x - rect.min_x) / tile_side_length).round() as usize;
+    let h_tiles = ((rect.max_y - rect.min_y) / tile_side_length).round() as usize;
+
+    for y in 0..h_tiles {
+        for x in 0..w_tiles {
+            use NineSlice::*;
+            let slice = match (x == 0, x == w_tiles - 1, y == 0, y == h_tiles - 1) {
+                (true, _, true, _) => UpperLeft,
+                (_, true, true, _) => UpperRight,
+                (_, _, true, _) => Upper,
+                (true, _, _, true) => LowerLeft,
+                (_, true, _, true) => LowerRight,
+                (_, _, _, true) => Lower,
+                (true, _, _, _) => Left,
+                (_, true, _, _) => Right,
+                _ => NoEdges,
+            };
+
+            commands.push(draw::Command::Sprite(SpriteSpec{
+                sprite: SpriteKind::NineSlice(slice, border_kind),
+                xy: DrawXY {
+                    x: rect.min_x + x as DrawLength * tile_side_length,
+                    y: rect.min_y + y as DrawLength * tile_side_length,
+                },
+                wh: ui_tile_draw_wh,
+            }));
+        }
+    }
+}
+
+/// The label, or the label number if the label is empty.
+fn label_or_index(labels: &[Label], i: usize) -> String {
+    match labels.get(i) {
+        Some(label) if !label.is_empty() => label.clone(),
+        _ => i.to_string(),
+    }
+}
+
+/// The labels of the column and row of the tile, for display.
+fn pair_text(board: &Board, txy: tile::XY) -> String {
+    let (column, row) = txy.column_and_row();
+
+    format!(
+        "{} × {}",
+        label_or_index(board.labels(Axis::Columns), column),
+        label_or_index(board.labels(Axis::Rows), row),
+    )
+}
+
+pub fn update(
+    state: &mut State,
+    commands: &mut dyn ClearableStorage<draw::Command>,
+    input_flags: InputFlags,
+    text_input: TextInput,
+    text_width: &dyn Fn(&str, draw::TextKind) -> DrawLength,
+    cursor_xy: CursorXY,
+    draw_wh: DrawWH,
+) {
+    use draw::{
+        BarKind,
+        BarSpec,
+        CursorSpec,
+        OverlayKind,
+        OverlaySpec,
+        TextSpec,
+        TextKind,
+        Command::*,
+    };
+
+    // Undo before anything else, since it can change the number of tiles.
+    if input_flags & INPUT_REDO_PRESSED != 0 {
+        state.redo();
+    } else if input_flags & INPUT_UNDO_PRESSED != 0 {
+        state.undo();
+    }
+
+    let tile_wh = state.board.tiles.wh;
+
+    if draw_wh != state.ui.sizes.draw_wh
+    || tile_wh != state.ui.sizes.tile_wh {
+        state.ui.sizes = draw::fresh_sizes(draw_wh, tile_wh);
+    }
+    state.ui.cursor_xy = cursor_xy;
+
+    let left_mouse_button_down = input_flags & INPUT_LEFT_MOUSE_DOWN != 0;
+
+    state.ui.left_mouse_button =
+        if left_mouse_button_down {
+            ButtonState::Down
+        } else {
+            ButtonState::Up
+        };
+
+    commands.clear();
+
+    let input = state.ui.held_directions.input(input_flags, state.ui.eye_movement);
+
+    use EyeState::*;
+    use Input::*;
+    use crate::Dir::*;
 
     const HOLD_FRAMES: AnimationTimer = 30;
 
@@ -622,14 +2770,15 @@ pub fn update(
     );
 
     if left_mouse_button_pressed {
-        state.ui.last_pressed = state.ui.click_area();
+        state.ui.last_pressed = state.ui.click_area(&state.board);
     }
 
     macro_rules! on_clicked {
         (| $click_area: ident | $code: block) => {
             if left_mouse_button_released {
-                if state.ui.click_area().is_some()
-                && state.ui.last_pressed == state.ui.click_area() {
+                let click_area = state.ui.click_area(&state.board);
+                if click_area.is_some()
+                && state.ui.last_pressed == click_area {
                     match state.ui.last_pressed {
                         None => {
                             panic!("unexpected last_pressed state");
@@ -645,6 +2794,18 @@ pub fn update(
         }
     }
 
+    let last_label_page = match state.ui.mode {
+        UiMode::Checking | UiMode::EditNote(_) => 0,
+        UiMode::EditLabels(axis) => {
+            state.board.labels(axis).len().saturating_sub(1) / LABELS_PER_PAGE
+        },
+    };
+    if state.ui.label_page > last_label_page {
+        state.ui.label_page = last_label_page;
+    }
+
+    let mode_before = state.ui.mode;
+
     match state.ui.mode {
         UiMode::Checking => {
             match input {
@@ -695,206 +2856,954 @@ pub fn update(
                 Dir(UpRight) => {
                     state.board.eye.state = Moved(UpRight);
                     state.board.eye.xy.move_up();
-                    state.board.eye.xy.move_right();
+                    state.board.eye.xy.move_right(tile_wh);
                 },
                 Dir(Right) => {
                     state.board.eye.state = Moved(Right);
-                    state.board.eye.xy.move_right();
+                    state.board.eye.xy.move_right(tile_wh);
                 },
                 Dir(DownRight) => {
                     state.board.eye.state = Moved(DownRight);
-                    state.board.eye.xy.move_down();
-                    state.board.eye.xy.move_right();
+                    state.board.eye.xy.move_down(tile_wh);
+                    state.board.eye.xy.move_right(tile_wh);
                 },
                 Dir(Down) => {
                     state.board.eye.state = Moved(Down);
-                    state.board.eye.xy.move_down();
+                    state.board.eye.xy.move_down(tile_wh);
                 },
                 Dir(DownLeft) => {
                     state.board.eye.state = Moved(DownLeft);
-                    state.board.eye.xy.move_down();
+                    state.board.eye.xy.move_down(tile_wh);
                     state.board.eye.xy.move_left();
                 },
                 Dir(Left) => {
                     state.board.eye.state = Moved(Left);
                     state.board.eye.xy.x = state.board.eye.xy.x.saturating_sub_one();
                 },
-                Dir(UpLeft) => {
-                    state.board.eye.state = Moved(UpLeft);
-                    state.board.eye.xy.move_up();
-                    state.board.eye.xy.move_left();
+                Dir(UpLeft) => {
+                    state.board.eye.state = Moved(UpLeft);
+                    state.board.eye.xy.move_up();
+                    state.board.eye.xy.move_left();
+                },
+                Interact => {
+                    state.board.eye.state = SmallPupil;
+
+                    let txy = state.board.eye.xy;
+                    let (column, row) = txy.column_and_row();
+                    if state.board.is_interactive(column, row) {
+
+                        let i = tile::xy_to_i(txy, tile_wh);
+                        let next = state.board.tiles.tiles[i].status.next();
+
+                        // This also sets the mirrored tile, if any.
+                        let _ = state.edit_board(|board| {
+                            board.set_status(column, row, next)
+                        });
+                    }
+                },
+            }
+
+            if input_flags & INPUT_SYMMETRY_PRESSED != 0 {
+                let next = match state.board.symmetry {
+                    Symmetry::Asymmetric => Symmetry::Symmetric(<_>::default()),
+                    Symmetry::Symmetric(layout) => match layout.mirror_tiles {
+                        MirrorTiles::Dimmed => Symmetry::Symmetric(SymmetricLayout {
+                            mirror_tiles: MirrorTiles::Hidden,
+                            ..layout
+                        }),
+                        MirrorTiles::Hidden => Symmetry::Asymmetric,
+                    },
+                };
+
+                // Non-square boards just stay asymmetric.
+                let _ = state.edit_board(|board| board.set_symmetry(next));
+            }
+
+            if let (Symmetry::Symmetric(mut layout), true) = (
+                state.board.symmetry,
+                input_flags & (INPUT_TRIANGLE_PRESSED | INPUT_DIAGONAL_PRESSED) != 0,
+            ) {
+                if input_flags & INPUT_TRIANGLE_PRESSED != 0 {
+                    layout.kept = match layout.kept {
+                        Triangle::Upper => Triangle::Lower,
+                        Triangle::Lower => Triangle::Upper,
+                    };
+                }
+
+                if input_flags & INPUT_DIAGONAL_PRESSED != 0 {
+                    layout.diagonal = match layout.diagonal {
+                        Diagonal::Included => Diagonal::Excluded,
+                        Diagonal::Excluded => Diagonal::NotApplicable,
+                        Diagonal::NotApplicable => Diagonal::Included,
+                    };
+                }
+
+                // Already symmetric, so already square.
+                let _ = state.edit_board(|board| {
+                    board.set_symmetry(Symmetry::Symmetric(layout))
+                });
+            }
+
+            if input_flags & INPUT_NOTE_PRESSED != 0 {
+                let txy = match state.ui.click_area(&state.board) {
+                    Some(ClickArea::TileXY(txy)) => txy,
+                    _ => state.board.eye.xy,
+                };
+
+                state.ui.mode = UiMode::EditNote(txy);
+
+                let (column, row) = txy.column_and_row();
+                let note = state.board.note(column, row).unwrap_or_default();
+                state.ui.note_cursor = text::Cursor::at(note.len());
+            }
+
+            let hovered_txy = match state.ui.click_area(&state.board) {
+                Some(ClickArea::TileXY(txy)) => Some(txy),
+                _ => None,
+            };
+
+            macro_rules! status_at {
+                ($txy: expr) => {
+                    state.board.tiles.tiles[tile::xy_to_i($txy, tile_wh)].status
+                }
+            }
+
+            if left_mouse_button_pressed {
+                let shift_down = input_flags & INPUT_SHIFT_DOWN != 0;
+
+                match hovered_txy {
+                    Some(txy) if shift_down => {
+                        state.ui.selection = Some(Selection {
+                            anchor: txy,
+                            corner: txy,
+                        });
+                        state.ui.drag = Some(Drag::Select);
+                        // So releasing the button does not count as a click.
+                        state.ui.last_pressed = None;
+                    },
+                    // Clicking on the selection sets all of it, on release.
+                    Some(txy) if state.ui.is_selected(txy) => {},
+                    Some(txy) => {
+                        state.ui.selection = None;
+                        state.ui.drag = Some(Drag::Paint {
+                            from: txy,
+                            last: txy,
+                            status: status_at!(txy).next(),
+                            started: false,
+                        });
+                    },
+                    // Pressing on empty space clears the selection. Pressing
+                    // on labels and buttons leaves it for them to act on.
+                    None => if state.ui.click_area(&state.board).is_none() {
+                        state.ui.selection = None;
+                    },
+                }
+            }
+
+            if state.ui.selection.is_some() {
+                if input_flags & INPUT_CHECK_ALL_PRESSED != 0 {
+                    state.set_selected_statuses(Status::Passes);
+                }
+
+                if input_flags & INPUT_CLEAR_ALL_PRESSED != 0 {
+                    state.set_selected_statuses(Status::Unchecked);
+                }
+            }
+
+            match (state.ui.drag, left_mouse_button_down) {
+                (Some(Drag::Select), true) => {
+                    // Tiles that cannot be clicked on can still be a corner.
+                    let cursor_txy = tile_xy_from_draw(&state.ui.sizes, state.ui.cursor_xy);
+
+                    if let (Some(selection), Some(txy)) = (&mut state.ui.selection, cursor_txy) {
+                        selection.corner = txy;
+                    }
+                },
+                (Some(Drag::Paint { from, last, status, started }), true) => {
+                    if let Some(txy) = hovered_txy {
+                        let started = started || txy != from;
+
+                        if started {
+                            state.ui.drag = Some(Drag::Paint { from, last: txy, status, started });
+                            // So releasing the button does not count as a click.
+                            state.ui.last_pressed = None;
+
+                            // Tiles that cannot be clicked on are skipped, like
+                            // with the selection.
+                            let line: Vec<_> = tiles_on_line(
+                                last.column_and_row(),
+                                txy.column_and_row(),
+                            ).into_iter()
+                                .filter(|&(column, row)| state.board.is_interactive(column, row))
+                                .collect();
+
+                            if line.iter().any(|&(column, row)| {
+                                state.board.status(column, row) != Ok(status)
+                            }) {
+                                state.edit_board_coalescing(
+                                    Some(history::Coalesce::Paint),
+                                    |board| {
+                                        for (column, row) in line {
+                                            // This also sets the mirrored tile, if any.
+                                            let _ = board.set_status(column, row, status);
+                                        }
+                                    },
+                                );
+                            }
+                        }
+                    }
                 },
-                Interact => {
-                    state.board.eye.state = SmallPupil;
+                (Some(_), false) => {
+                    state.ui.drag = None;
+                    // So the next drag is a separate step.
+                    state.history.break_coalescing();
                 },
+                (None, _) => {},
+            }
+
+            if input_flags & INPUT_RIGHT_MOUSE_PRESSED != 0 {
+                if let Some(txy) = hovered_txy {
+                    let previous = status_at!(txy).previous();
+
+                    if state.ui.is_selected(txy) {
+                        state.set_selected_statuses(previous);
+                    } else {
+                        let (column, row) = txy.column_and_row();
+
+                        let _ = state.edit_board(|board| {
+                            board.set_status(column, row, previous)
+                        });
+                    }
+                }
             }
 
             on_clicked!(
                 |area| {
                     match *area {
                         ClickArea::TileXY(txy) => {
-                            let i = tile::xy_to_i(txy);
-
-                            state.board.tiles.tiles[i] = match state.board.tiles.tiles[i] {
-                                TileData::Checked => TileData::Unchecked,
-                                TileData::Unchecked => TileData::Checked,
-                            };
+                            let next = status_at!(txy).next();
+
+                            if state.ui.is_selected(txy) {
+                                state.set_selected_statuses(next);
+                            } else {
+                                let (column, row) = txy.column_and_row();
+
+                                // This also sets the mirrored tile, if any.
+                                let _ = state.edit_board(|board| {
+                                    board.set_status(column, row, next)
+                                });
+                            }
                         },
-                        ClickArea::Labels => {
-                            state.ui.mode = UiMode::EditLabels;
+                        ClickArea::Label(axis, index) => {
+                            let shift_down = input_flags & INPUT_SHIFT_DOWN != 0;
+                            let is_already_selected = state.selection()
+                                == Selection::line(axis, index, tile_wh)
+                                    .map(Selection::ranges);
+
+                            if is_already_selected && !shift_down {
+                                state.cycle_selected_statuses();
+                            } else {
+                                let _ = state.select_line(axis, index, shift_down);
+                            }
                         }
+                        ClickArea::EditLabelsButton(axis) => {
+                            state.ui.mode = UiMode::EditLabels(axis);
+                            state.ui.label_page = 0;
+                        }
+                        ClickArea::PreviousLabelPage
+                        | ClickArea::NextLabelPage
+                        | ClickArea::LineButton(_) => {}
                     }
                 }
             );
         },
-        UiMode::EditLabels => {
+        UiMode::EditLabels(edited_axis) => {
             on_clicked!(
                 |area| {
-                    match area {
+                    match *area {
                         ClickArea::TileXY(_) => {},
-                        ClickArea::Labels => {
-                            // Will probably want a close button instead.
-                            state.ui.mode = UiMode::Checking;
+                        ClickArea::Label(axis, index) => {
+                            // Go to the page with the clicked label on it.
+                            state.ui.mode = UiMode::EditLabels(axis);
+                            state.ui.label_page = index / LABELS_PER_PAGE;
+                        }
+                        ClickArea::EditLabelsButton(axis) => {
+                            state.ui.mode = if axis == edited_axis {
+                                UiMode::Checking
+                            } else {
+                                UiMode::EditLabels(axis)
+                            };
+                            state.ui.label_page = 0;
+                        }
+                        ClickArea::PreviousLabelPage => {
+                            state.ui.label_page =
+                                state.ui.label_page.saturating_sub(1);
+                        }
+                        ClickArea::NextLabelPage => {
+                            if state.ui.label_page < last_label_page {
+                                state.ui.label_page += 1;
+                            }
+                        }
+                        ClickArea::LineButton(button) => {
+                            let op = state.ui.focused_label.as_ref()
+                                .and_then(|f| button.op(
+                                    f.index,
+                                    state.board.labels(edited_axis).len(),
+                                ));
+
+                            if let Some((op, focus_index)) = op {
+                                if state.edit_line(edited_axis, op).is_ok() {
+                                    state.focus_label(edited_axis, focus_index, false);
+                                    state.ui.label_page = focus_index / LABELS_PER_PAGE;
+                                }
+                            }
                         }
                     }
                 }
             );
 
-            for (i, label) in state.board.labels.iter_mut().enumerate() {
-                let text_box_rect = text_box_rect(&state.ui.sizes, i);
+            let hovered_index = (0..LABELS_PER_PAGE)
+                .find(|&i| {
+                    text_box_rect(&state.ui.sizes, i).contains(state.ui.cursor_xy)
+                })
+                .map(|i| state.ui.label_page * LABELS_PER_PAGE + i)
+                .filter(|&index| index < state.board.labels(edited_axis).len());
 
-                if text_box_rect.contains(state.ui.cursor_xy) {
-                    for byte in text_input {
-                        match byte {
-                            0 => break,
-                            // backspace
-                            8
-                            // delete
-                            | 127 => { label.pop(); }
-                            _ => { label.push(byte as char); }
-                        }
-                    }
+            let label_count = state.board.labels(edited_axis).len();
+
+            if state.ui.focused_label.as_ref()
+                .is_some_and(|f| f.axis != edited_axis || f.index >= label_count) {
+                state.unfocus_label();
+            }
+
+            if left_mouse_button_pressed {
+                match hovered_index {
+                    Some(index) => if state.ui.focused_label.as_ref()
+                        .map(|f| f.index) != Some(index) {
+                        state.focus_label(edited_axis, index, false);
+                    },
+                    // The line buttons act on the focused label, so pressing
+                    // them keeps it.
+                    None => if !matches!(
+                        state.ui.click_area(&state.board),
+                        Some(ClickArea::LineButton(_))
+                    ) {
+                        state.unfocus_label();
+                    },
+                }
+            }
+
+            if text_input.keys & TEXT_TAB != 0 {
+                let backwards = text_input.keys & TEXT_SHIFT_DOWN != 0;
+
+                let index = match (state.ui.focused_label.as_ref(), backwards) {
+                    (Some(f), false) => (f.index + 1) % label_count,
+                    (Some(f), true) => (f.index + label_count - 1) % label_count,
+                    (None, false) => state.ui.label_page * LABELS_PER_PAGE,
+                    (None, true) => (
+                        (state.ui.label_page + 1) * LABELS_PER_PAGE
+                    ).min(label_count) - 1,
+                };
+
+                state.focus_label(edited_axis, index, true);
+                state.ui.label_page = index / LABELS_PER_PAGE;
+            }
+
+            if text_input.keys & TEXT_ESCAPE != 0 {
+                if let Some(focused) = state.ui.focused_label.as_ref() {
+                    let index = focused.index;
+                    let original = focused.original.clone();
+
+                    // Folds into the step with the typing in it, if any, which
+                    // leaves nothing to undo.
+                    state.edit_board_coalescing(
+                        Some(history::Coalesce::Label(edited_axis, index)),
+                        |board| board.labels_mut(edited_axis)[index] = original,
+                    );
+                    state.unfocus_label();
+                }
+            } else if text_input.keys & TEXT_ENTER != 0 {
+                state.unfocus_label();
+            }
+
+            if let Some(focused) = state.ui.focused_label.as_mut() {
+                let index = focused.index;
+                let mut label = state.board.labels(edited_axis)[index].clone();
+
+                let cursor_before = focused.cursor;
+
+                if (left_mouse_button_pressed && hovered_index == Some(index))
+                || focused.dragging {
+                    let rect = text_box_rect(
+                        &state.ui.sizes,
+                        index % LABELS_PER_PAGE,
+                    );
+
+                    focused.cursor.click(
+                        &label,
+                        state.ui.cursor_xy.x
+                            - (rect.min_x + state.ui.sizes.text_box_margin),
+                        &|s| text_width(s, draw::TextKind::TextBox),
+                        focused.dragging
+                        || text_input.keys & TEXT_SHIFT_DOWN != 0,
+                    );
+
+                    // Keep selecting in the same label while the mouse is
+                    // held, even if it leaves the label.
+                    focused.dragging = left_mouse_button_down;
                 }
+
+                let edit = focused.cursor.edit(&mut label, &text_input, false);
+
+                if edit.moved || focused.cursor != cursor_before {
+                    focused.idle_frames = 0;
+                } else {
+                    focused.idle_frames = focused.idle_frames.wrapping_add(1);
+                }
+
+                if edit.copied.is_some() {
+                    state.ui.copied_text = edit.copied;
+                }
+
+                if edit.changed {
+                    state.edit_board_coalescing(
+                        Some(history::Coalesce::Label(edited_axis, index)),
+                        |board| board.labels_mut(edited_axis)[index] = label,
+                    );
+                }
+            }
+        }
+        UiMode::EditNote(txy) => {
+            // Pressing outside the note closes it. Forgetting the press means
+            // releasing the button later does not count as a click on
+            // whatever is under the cursor.
+            if left_mouse_button_pressed
+            && !note_box_rect(&state.ui.sizes).contains(state.ui.cursor_xy) {
+                state.ui.mode = UiMode::Checking;
+                state.ui.last_pressed = None;
+            }
+
+            if text_input.keys & TEXT_ESCAPE != 0 {
+                state.ui.mode = UiMode::Checking;
+            }
+
+            let (column, row) = txy.column_and_row();
+
+            let mut note = state.board.note(column, row)
+                .unwrap_or_default()
+                .to_owned();
+
+            // Notes are edited from the end, since they can wrap onto
+            // several lines, so only the keys that do not move the caret
+            // apply. The note may have been changed by an undo since the
+            // last frame.
+            let mut cursor = state.ui.note_cursor;
+            cursor.clamp(&note);
+            cursor.caret = note.len();
+
+            let edit = cursor.edit(
+                &mut note,
+                &TextInput {
+                    keys: text_input.keys & (
+                        TEXT_CTRL_DOWN
+                        | TEXT_SELECT_ALL
+                        | TEXT_COPY
+                        | TEXT_CUT
+                        | TEXT_ENTER
+                    ),
+                    ..text_input
+                },
+                true,
+            );
+
+            state.ui.note_cursor = cursor;
+
+            if edit.copied.is_some() {
+                state.ui.copied_text = edit.copied;
+            }
+
+            if edit.changed {
+                // This also sets the note of the mirrored tile, if any.
+                let _ = state.edit_board_coalescing(
+                    Some(history::Coalesce::Note(tile::xy_to_i(txy, tile_wh))),
+                    |board| board.set_note(column, row, note),
+                );
             }
         }
     }
 
+    // Typing after leaving and coming back to the same text is a new step.
+    if state.ui.mode != mode_before {
+        state.history.break_coalescing();
+    }
+
+    if !matches!(state.ui.mode, UiMode::EditLabels(_)) {
+        state.ui.focused_label = None;
+    }
+
+    // The tile the cursor is over, for the crosshair and the tooltip.
+    let hovered_txy = match (state.ui.mode, state.ui.click_area(&state.board)) {
+        (UiMode::Checking, Some(ClickArea::TileXY(txy))) => Some(txy),
+        _ => None,
+    };
+
     match state.ui.mode {
         UiMode::Checking => {
-            for i in 0..TILES_LENGTH {
-                let tile_data = state.board.tiles.tiles[i];
+            let tile_draw_wh = tile_draw_wh(&state.ui.sizes);
+
+            for (i, tile_data) in state.board.tiles.tiles.iter().enumerate() {
+                let txy = tile::i_to_xy(i, state.board.tiles.wh);
+
+                let (column, row) = txy.column_and_row();
+                let role = state.board.tile_role(column, row);
+
+                let sprite_fn = match role {
+                    TileRole::Mirror(MirrorTiles::Hidden)
+                    | TileRole::ExcludedDiagonal => continue,
+                    TileRole::NotApplicableDiagonal => SpriteKind::NotApplicable,
+                    TileRole::Counted
+                    | TileRole::Mirror(MirrorTiles::Dimmed) => tile_data.status.sprite_fn(),
+                };
 
-                let txy = tile::i_to_xy(i);
+                let xy = draw_xy_from_tile(&state.ui.sizes, txy);
 
                 commands.push(Sprite(SpriteSpec{
-                    sprite: (tile_data.sprite_fn())(state.ui.tile_state(txy)),
-                    xy: draw_xy_from_tile(&state.ui.sizes, txy),
+                    sprite: sprite_fn(state.ui.tile_state(txy)),
+                    xy,
+                    wh: tile_draw_wh,
                 }));
+
+                if !tile_data.note.is_empty() {
+                    commands.push(Sprite(SpriteSpec{
+                        sprite: SpriteKind::NoteMarker,
+                        xy,
+                        wh: tile_draw_wh,
+                    }));
+                }
+
+                if let TileRole::Mirror(MirrorTiles::Dimmed) = role {
+                    commands.push(Overlay(OverlaySpec{
+                        kind: OverlayKind::Dim,
+                        xy,
+                        wh: tile_draw_wh,
+                    }));
+                }
+
+                if state.ui.is_selected(txy) {
+                    commands.push(Overlay(OverlaySpec{
+                        kind: OverlayKind::Selected,
+                        xy,
+                        wh: tile_draw_wh,
+                    }));
+                }
+
+                let is_in_crosshair = hovered_txy.is_some_and(|hovered| {
+                    hovered != txy
+                    && (hovered.x == txy.x || hovered.y == txy.y)
+                });
+                if is_in_crosshair {
+                    commands.push(Overlay(OverlaySpec{
+                        kind: OverlayKind::Crosshair,
+                        xy,
+                        wh: tile_draw_wh,
+                    }));
+                }
             }
 
+            let eye_xy = draw_xy_from_tile(&state.ui.sizes, state.board.eye.xy);
+
+            commands.push(Overlay(OverlaySpec{
+                kind: OverlayKind::Focus,
+                xy: eye_xy,
+                wh: tile_draw_wh,
+            }));
+
             commands.push(Sprite(SpriteSpec{
                 sprite: state.board.eye.state.sprite(),
-                xy: draw_xy_from_tile(&state.ui.sizes, state.board.eye.xy),
+                xy: eye_xy,
+                wh: tile_draw_wh,
             }));
         },
-        UiMode::EditLabels => {
+        UiMode::EditLabels(_) | UiMode::EditNote(_) => {
             state.board.eye.state = Idle;
         },
     }
 
+    // Lines may have been added or removed above.
+    let tile_wh = state.board.tiles.wh;
+    if tile_wh != state.ui.sizes.tile_wh {
+        state.ui.sizes = draw::fresh_sizes(draw_wh, tile_wh);
+    }
+
     // Drawing labelled edges
     {
         let label_wh = label_wh(&state.ui.sizes);
-        let top_label_rect = top_label_rect(&state.ui.sizes);
-        let left_label_rect = left_label_rect(&state.ui.sizes);
-
-        let mut top_x = top_label_rect.min_x;
-        let top_y = top_label_rect.min_y;
 
-        let left_x = left_label_rect.min_x;
-        let mut left_y = left_label_rect.min_y;
+        for axis in [Axis::Columns, Axis::Rows] {
+            let label_rect = match axis {
+                Axis::Columns => top_label_rect(&state.ui.sizes),
+                Axis::Rows => left_label_rect(&state.ui.sizes),
+            };
 
-        let slice_kind = if
-            top_label_rect.contains(state.ui.cursor_xy)
-            || left_label_rect.contains(state.ui.cursor_xy) {
-            BorderKind::YellowEdge
-        } else {
-            BorderKind::WhiteEdge
-        };
+            let is_edited = match state.ui.mode {
+                UiMode::Checking | UiMode::EditNote(_) => false,
+                UiMode::EditLabels(edited_axis) => edited_axis == axis,
+            };
 
-        for (i, label) in state.board.labels.iter().enumerate() {
-            // background
-            let top_slice = if i == 0 {
-                NineSlice::UpperLeft
-            } else if i == state.board.labels.len() - 1 {
-                NineSlice::UpperRight
-            } else {
-                NineSlice::Upper
+            // Which labels have all of their line selected.
+            let selected_lines = match state.selection() {
+                Some((columns, rows)) => match axis {
+                    Axis::Columns if rows == (0..=tile_wh.h() as usize - 1) => Some(columns),
+                    Axis::Rows if columns == (0..=tile_wh.w() as usize - 1) => Some(rows),
+                    Axis::Columns | Axis::Rows => None,
+                },
+                None => None,
             };
-            let left_slice = if i == 0 {
-                NineSlice::UpperLeft
-            } else if i == state.board.labels.len() - 1 {
-                NineSlice::LowerLeft
+
+            let labels = state.board.labels(axis);
+
+            let mut xy = DrawXY { x: label_rect.min_x, y: label_rect.min_y };
+
+            for (i, label) in labels.iter().enumerate() {
+                let is_in_crosshair = hovered_txy.is_some_and(|txy| {
+                    let (column, row) = txy.column_and_row();
+                    match axis {
+                        Axis::Columns => column == i,
+                        Axis::Rows => row == i,
+                    }
+                });
+
+                let slice_kind = if is_edited
+                || is_in_crosshair
+                || selected_lines.as_ref().is_some_and(|lines| lines.contains(&i))
+                || state.ui.is_hovered(ClickArea::Label(axis, i)) {
+                    BorderKind::YellowEdge
+                } else {
+                    BorderKind::WhiteEdge
+                };
+
+                // background
+                let slice = match axis {
+                    Axis::Columns => if i == 0 {
+                        NineSlice::UpperLeft
+                    } else if i == labels.len() - 1 {
+                        NineSlice::UpperRight
+                    } else {
+                        NineSlice::Upper
+                    },
+                    Axis::Rows => if i == 0 {
+                        NineSlice::UpperLeft
+                    } else if i == labels.len() - 1 {
+                        NineSlice::LowerLeft
+                    } else {
+                        NineSlice::Left
+                    },
+                };
+
+                commands.push(Sprite(SpriteSpec{
+                    sprite: SpriteKind::NineSlice(slice, slice_kind),
+                    xy,
+                    wh: label_wh,
+                }));
+
+                // text
+                commands.push(Text(TextSpec {
+                    text: label.to_string(),
+                    xy,
+                    wh: label_wh,
+                    kind: TextKind::CellLabel,
+                }));
+
+                // How much of the line is checked, as a thin bar along the
+                // bottom of its label.
+                if let Ok(line_progress) = state.board.line_progress(axis, i) {
+                    let bar_h = (label_wh.h / 8.).floor().max(1.);
+
+                    commands.push(Bar(BarSpec{
+                        kind: BarKind::Checked,
+                        xy: DrawXY { x: xy.x, y: xy.y + label_wh.h - bar_h },
+                        wh: DrawWH { w: label_wh.w, h: bar_h },
+                        fraction: line_progress.fraction(),
+                    }));
+                }
+
+                match axis {
+                    Axis::Columns => { xy.x += label_wh.w; },
+                    Axis::Rows => { xy.y += label_wh.h; },
+                }
+            }
+
+            let button_rect = edit_labels_button_rect(&state.ui.sizes, axis);
+            let button_kind = if is_edited
+            || state.ui.is_hovered(ClickArea::EditLabelsButton(axis)) {
+                BorderKind::YellowEdge
             } else {
-                NineSlice::Left
+                BorderKind::WhiteEdge
             };
 
-            commands.push(Sprite(SpriteSpec{
-                sprite: SpriteKind::NineSlice(top_slice, slice_kind),
-                xy: DrawXY { x: top_x, y: top_y },
-            }));
+            let ui_tile_draw_wh = ui_tile_draw_wh(&state.ui.sizes);
+            for i in 0..EDIT_LABELS_BUTTON_W_TILES {
+                let three_slice = if i == 0 {
+                    LRThreeSlice::Left
+                } else if i == EDIT_LABELS_BUTTON_W_TILES - 1 {
+                    LRThreeSlice::Right
+                } else {
+                    LRThreeSlice::Center
+                };
 
-            commands.push(Sprite(SpriteSpec{
-                sprite: SpriteKind::NineSlice(left_slice, slice_kind),
-                xy: DrawXY { x: left_x, y: left_y },
+                commands.push(Sprite(SpriteSpec{
+                    sprite: SpriteKind::LRThreeSlice(three_slice, button_kind),
+                    xy: DrawXY {
+                        x: button_rect.min_x + i as DrawLength * ui_tile_draw_wh.w,
+                        y: button_rect.min_y,
+                    },
+                    wh: ui_tile_draw_wh,
+                }));
+            }
+
+            commands.push(Text(TextSpec{
+                text: match (is_edited, axis) {
+                    (true, _) => "Done editing",
+                    (false, Axis::Columns) => "Edit column labels",
+                    (false, Axis::Rows) => "Edit row labels",
+                }.to_owned(),
+                xy: DrawXY { x: button_rect.min_x, y: button_rect.min_y },
+                wh: DrawWH {
+                    w: button_rect.max_x - button_rect.min_x,
+                    h: button_rect.max_y - button_rect.min_y,
+                },
+                kind: TextKind::OneTile,
             }));
+        }
+    }
+
+    // Drawing the progress panel
+    {
+        let rect = stats_panel_rect(&state.ui.sizes);
+        let tile_side_length = state.ui.sizes.ui_tile_side_length;
+        let w = rect.max_x - rect.min_x;
+
+        // Too narrow to fit anything useful.
+        if w >= (STATS_TEXT_W_TILES + 2) as DrawLength * tile_side_length {
+            push_panel(commands, &state.ui.sizes, &rect, BorderKind::WhiteEdge);
+
+            let mut y = rect.min_y + tile_side_length;
+            let x = rect.min_x + tile_side_length / 2.;
+            let inner_w = w - tile_side_length;
+            let bar_h = tile_side_length / 2.;
+
+            macro_rules! push_line {
+                ($text: expr) => {
+                    commands.push(Text(TextSpec{
+                        text: $text,
+                        xy: DrawXY { x, y },
+                        wh: DrawWH { w: inner_w, h: tile_side_length },
+                        kind: TextKind::OneTile,
+                    }));
+                    y += tile_side_length;
+                }
+            }
 
+            macro_rules! push_bar {
+                ($kind: expr, $fraction: expr) => {
+                    commands.push(Bar(BarSpec{
+                        kind: $kind,
+                        xy: DrawXY { x, y: y + bar_h / 2. },
+                        wh: DrawWH { w: inner_w, h: bar_h },
+                        fraction: $fraction,
+                    }));
+                    y += tile_side_length;
+                }
+            }
 
-            // text
-            let top_text_spec = TextSpec {
-                text: label.to_string(),
-                xy: DrawXY { x: top_x, y: top_y },
-                wh: label_wh,
-                kind: TextKind::CellLabel,
-            };
+            let progress = state.board.progress();
 
-            commands.push(Text(top_text_spec.clone()));
-            commands.push(Text(TextSpec {
-                xy: DrawXY { x: left_x, y: left_y },
-                ..top_text_spec
-            }));
+            push_line!(format!(
+                "{}/{} checked, {}%",
+                progress.checked,
+                progress.total,
+                progress.percent(),
+            ));
+            push_bar!(BarKind::Checked, progress.fraction());
+            push_line!(format!("{} remaining", progress.total - progress.checked));
+
+            for (status, count) in progress.counts.iter() {
+                commands.push(Text(TextSpec{
+                    text: format!("{} {count}", status.display_name()),
+                    xy: DrawXY { x, y },
+                    wh: DrawWH {
+                        w: STATS_TEXT_W_TILES as DrawLength * tile_side_length,
+                        h: tile_side_length,
+                    },
+                    kind: TextKind::OneTile,
+                }));
 
-            top_x += label_wh.w;
-            left_y += label_wh.h;
+                let bar_x = x + STATS_TEXT_W_TILES as DrawLength * tile_side_length;
+                commands.push(Bar(BarSpec{
+                    kind: BarKind::Status(status),
+                    xy: DrawXY { x: bar_x, y: y + bar_h / 2. },
+                    wh: DrawWH { w: rect.max_x - tile_side_length / 2. - bar_x, h: bar_h },
+                    fraction: if progress.total == 0 {
+                        0.
+                    } else {
+                        count as f32 / progress.total as f32
+                    },
+                }));
+
+                y += tile_side_length;
+            }
+
+            y += tile_side_length;
+
+            // The lines of the hovered tile, or of the eye's tile otherwise.
+            let (column, row) = hovered_txy
+                .unwrap_or(state.board.eye.xy)
+                .column_and_row();
+
+            for (axis, index, name) in [
+                (Axis::Rows, row, "Row"),
+                (Axis::Columns, column, "Column"),
+            ] {
+                let Ok(line_progress) = state.board.line_progress(axis, index) else {
+                    continue
+                };
+
+                push_line!(format!(
+                    "{name} {}: {}%",
+                    label_or_index(state.board.labels(axis), index),
+                    line_progress.percent(),
+                ));
+                push_bar!(BarKind::Checked, line_progress.fraction());
+            }
         }
     }
 
     match state.ui.mode {
-        UiMode::Checking => {/* no extra drawing in this layer yet */},
-        UiMode::EditLabels => {
-            let tile_side_length = state.ui.sizes.tile_side_length;
+        UiMode::Checking => {
+            if let Some(txy) = hovered_txy {
+                let rect = tooltip_rect(&state.ui.sizes);
+                let tile_side_length = state.ui.sizes.ui_tile_side_length;
+                let (column, row) = txy.column_and_row();
+
+                push_panel(commands, &state.ui.sizes, &rect, BorderKind::WhiteEdge);
+
+                // The full labels, since the ones along the edges of the board
+                // are cut off when they do not fit.
+                commands.push(Text(TextSpec{
+                    text: pair_text(&state.board, txy),
+                    xy: DrawXY { x: rect.min_x, y: rect.min_y },
+                    wh: DrawWH {
+                        w: rect.max_x - rect.min_x,
+                        h: rect.max_y - rect.min_y - tile_side_length,
+                    },
+                    kind: TextKind::TextBox,
+                }));
+
+                let status = state.board.status(column, row).unwrap_or_default();
+                commands.push(Text(TextSpec{
+                    text: status.display_name().to_owned(),
+                    xy: DrawXY { x: rect.min_x, y: rect.max_y - tile_side_length },
+                    wh: DrawWH { w: rect.max_x - rect.min_x, h: tile_side_length },
+                    kind: TextKind::OneTile,
+                }));
+            }
+
+            let hovered_note = hovered_txy.and_then(|txy| {
+                let (column, row) = txy.column_and_row();
+                state.board.note(column, row).ok()
+                    .filter(|note| !note.is_empty())
+            });
+
+            if let Some(note) = hovered_note {
+                let rect = note_preview_rect(&state.ui.sizes);
+                let tile_side_length = state.ui.sizes.ui_tile_side_length;
+
+                commands.push(Text(TextSpec{
+                    text: "Note:".to_owned(),
+                    xy: DrawXY { x: rect.min_x, y: rect.min_y - tile_side_length },
+                    wh: DrawWH { w: rect.max_x - rect.min_x, h: tile_side_length },
+                    kind: TextKind::OneTile,
+                }));
+
+                push_panel(commands, &state.ui.sizes, &rect, BorderKind::WhiteEdge);
+
+                commands.push(Text(TextSpec{
+                    text: note.to_owned(),
+                    xy: DrawXY { x: rect.min_x, y: rect.min_y },
+                    wh: DrawWH {
+                        w: rect.max_x - rect.min_x,
+                        h: rect.max_y - rect.min_y,
+                    },
+                    kind: TextKind::TextBox,
+                }));
+            }
+        },
+        UiMode::EditNote(txy) => {
+            let rect = note_box_rect(&state.ui.sizes);
+            let tile_side_length = state.ui.sizes.ui_tile_side_length;
+
+            commands.push(Text(TextSpec{
+                text: format!("Note for {}:", pair_text(&state.board, txy)),
+                xy: DrawXY { x: rect.min_x, y: rect.min_y - tile_side_length },
+                wh: DrawWH { w: rect.max_x - rect.min_x, h: tile_side_length },
+                kind: TextKind::OneTile,
+            }));
+
+            push_panel(commands, &state.ui.sizes, &rect, BorderKind::BlueEdge);
+
+            let (column, row) = txy.column_and_row();
+
+            let note = state.board.note(column, row).unwrap_or_default();
+
+            commands.push(Text(TextSpec{
+                text: note.to_owned(),
+                xy: DrawXY { x: rect.min_x, y: rect.min_y },
+                wh: DrawWH {
+                    w: rect.max_x - rect.min_x,
+                    h: rect.max_y - rect.min_y,
+                },
+                kind: TextKind::TextBoxWithCursor(CursorSpec {
+                    caret: state.ui.note_cursor.caret,
+                    anchor: state.ui.note_cursor.anchor,
+                    is_caret_shown: is_caret_shown(state.animation_timer),
+                }),
+            }));
+        },
+        UiMode::EditLabels(edited_axis) => {
+            let tile_side_length = state.ui.sizes.ui_tile_side_length;
+            let ui_tile_draw_wh = ui_tile_draw_wh(&state.ui.sizes);
             let section_h = tile_side_length;
 
-            for (i, label) in state.board.labels.iter().enumerate() {
+            let first_index = state.ui.label_page * LABELS_PER_PAGE;
+
+            for (i, label) in state.board.labels(edited_axis).iter()
+                .skip(first_index)
+                .take(LABELS_PER_PAGE)
+                .enumerate() {
                 let text_box_rect = text_box_rect(&state.ui.sizes, i);
 
                 let label_x = text_box_rect.min_x;
-                let x = label_x - state.ui.sizes.tile_side_length;
+                let x = label_x - tile_side_length;
                 let y = text_box_rect.min_y;
 
+                let label_index = first_index + i;
+
                 commands.push(Text(TextSpec{
-                    text: format!("{i}:"), // TODO could make n static strs
+                    text: format!("{label_index}:"), // TODO could make n static strs
                     xy: DrawXY { x, y },
                     wh: DrawWH {
-                        w: state.ui.sizes.board_xywh.w,
+                        w: text_box_rect.max_x - x,
                         h: section_h
                     },
                     kind: TextKind::OneTile,
                 }));
 
-                let is_hovered = text_box_rect.contains(state.ui.cursor_xy);
+                let focused = state.ui.focused_label.as_ref()
+                    .filter(|f| f.axis == edited_axis && f.index == label_index);
 
-                let border_kind = if is_hovered {
+                let border_kind = if focused.is_some() {
+                    BorderKind::BlueEdge
+                } else if text_box_rect.contains(state.ui.cursor_xy) {
                     BorderKind::YellowEdge
                 } else {
                     BorderKind::WhiteEdge
@@ -918,6 +3827,7 @@ pub fn update(
                             x: label_x + i as DrawLength * tile_side_length,
                             y
                         },
+                        wh: ui_tile_draw_wh,
                     }));
                 }
 
@@ -926,17 +3836,80 @@ pub fn update(
                         text: label.to_string(), // TODO Copy-on-write in this case?
                         xy: DrawXY { x: label_x, y },
                         wh: DrawWH {
-                            w: state.ui.sizes.board_xywh.w,
+                            w: text_box_rect.max_x - label_x,
                             h: section_h
                         },
-                        kind: if is_hovered {
-                            TextKind::TextBoxWithCursor
-                        } else {
-                            TextKind::TextBox
+                        kind: match focused {
+                            Some(f) => TextKind::TextBoxWithCursor(CursorSpec {
+                                caret: f.cursor.caret,
+                                anchor: f.cursor.anchor,
+                                is_caret_shown: f.is_caret_shown(),
+                            }),
+                            None => TextKind::TextBox,
                         },
                     }
                 ));
             }
+
+            if last_label_page > 0 {
+                for (dir, is_usable) in [
+                    (Up, state.ui.label_page > 0),
+                    (Down, state.ui.label_page < last_label_page),
+                ] {
+                    let rect = label_page_button_rect(&state.ui.sizes, dir);
+
+                    commands.push(Sprite(SpriteSpec{
+                        sprite: SpriteKind::Arrow(
+                            dir,
+                            if is_usable {
+                                ArrowKind::Green
+                            } else {
+                                ArrowKind::Red
+                            }
+                        ),
+                        xy: DrawXY { x: rect.min_x, y: rect.min_y },
+                        wh: ui_tile_draw_wh,
+                    }));
+                }
+            }
+
+            for button in state.ui.usable_line_buttons(&state.board) {
+                let rect = line_button_rect(&state.ui.sizes, button);
+                let border_kind = if state.ui.is_hovered(ClickArea::LineButton(button)) {
+                    BorderKind::YellowEdge
+                } else {
+                    BorderKind::WhiteEdge
+                };
+
+                for i in 0..EDIT_LABELS_BUTTON_W_TILES {
+                    let three_slice = if i == 0 {
+                        LRThreeSlice::Left
+                    } else if i == EDIT_LABELS_BUTTON_W_TILES - 1 {
+                        LRThreeSlice::Right
+                    } else {
+                        LRThreeSlice::Center
+                    };
+
+                    commands.push(Sprite(SpriteSpec{
+                        sprite: SpriteKind::LRThreeSlice(three_slice, border_kind),
+                        xy: DrawXY {
+                            x: rect.min_x + i as DrawLength * tile_side_length,
+                            y: rect.min_y,
+                        },
+                        wh: ui_tile_draw_wh,
+                    }));
+                }
+
+                commands.push(Text(TextSpec{
+                    text: button.text(edited_axis).to_owned(),
+                    xy: DrawXY { x: rect.min_x, y: rect.min_y },
+                    wh: DrawWH {
+                        w: rect.max_x - rect.min_x,
+                        h: rect.max_y - rect.min_y,
+                    },
+                    kind: TextKind::OneTile,
+                }));
+            }
         },
     }
 
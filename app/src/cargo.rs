@@ -0,0 +1,159 @@
+//! Labels from the features of a Rust package, read from its `Cargo.toml`, for
+//! checking that every pair of features works together.
+
+use crate::Label;
+
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct FeatureOptions {
+    /// Also include the optional dependencies that Cargo makes features for.
+    /// Those that are only enabled through `dep:` in other features do not
+    /// get a feature of their own, so they are left out either way.
+    pub optional_dependencies: bool,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The manifest is not valid TOML. Holds the parser's message, which says
+    /// where the problem is.
+    Malformed(String),
+    /// The manifest has no features, or optional dependencies if those were
+    /// asked for.
+    NoFeatures,
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Malformed(message) => write!(f, "{message}"),
+            Self::NoFeatures => write!(f, "the manifest does not have any features"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// The names of the dependencies in `table`, a `[dependencies]` table or
+/// similar, that are marked as optional.
+fn optional_dependencies(table: Option<&toml::Value>) -> impl Iterator<Item = &str> {
+    table
+        .and_then(toml::Value::as_table)
+        .into_iter()
+        .flatten()
+        .filter(|(_, dependency)| {
+            dependency.get("optional").and_then(toml::Value::as_bool) == Some(true)
+        })
+        .map(|(name, _)| name.as_str())
+}
+
+/// Returns a label for each feature in the `[features]` table of `manifest`,
+/// which is the text of a `Cargo.toml` file, in the order they are listed.
+/// The `default` feature is left out, since it only turns on other features.
+pub fn feature_labels(
+    manifest: &str,
+    options: FeatureOptions,
+) -> Result<Vec<Label>, Error> {
+    let manifest: toml::Table = manifest.parse()
+        .map_err(|e: toml::de::Error| Error::Malformed(e.to_string()))?;
+
+    let features = manifest.get("features")
+        .and_then(toml::Value::as_table);
+
+    let mut labels: Vec<Label> = features
+        .into_iter()
+        .flatten()
+        .map(|(name, _)| name)
+        .filter(|&name| name != "default")
+        .cloned()
+        .collect();
+
+    if options.optional_dependencies {
+        let enabled_with_dep: Vec<&str> = features
+            .into_iter()
+            .flatten()
+            .filter_map(|(_, enables)| enables.as_array())
+            .flatten()
+            .filter_map(toml::Value::as_str)
+            .filter_map(|enabled| enabled.strip_prefix("dep:"))
+            .collect();
+
+        // Dependencies for particular targets can be optional too.
+        let targets = manifest.get("target")
+            .and_then(toml::Value::as_table)
+            .into_iter()
+            .flatten()
+            .filter_map(|(_, target)| target.as_table());
+
+        let tables = core::iter::once(&manifest)
+            .chain(targets)
+            .flat_map(|table| [
+                table.get("dependencies"),
+                table.get("build-dependencies"),
+            ]);
+
+        for table in tables {
+            for name in optional_dependencies(table) {
+                if !enabled_with_dep.contains(&name)
+                && !labels.iter().any(|label| label == name) {
+                    labels.push(name.to_owned());
+                }
+            }
+        }
+    }
+
+    if labels.is_empty() {
+        return Err(Error::NoFeatures);
+    }
+
+    Ok(labels)
+}
+
+#[cfg(test)]
+const EXAMPLE_MANIFEST: &str = r#"
+[package]
+name = "example"
+
+[dependencies]
+serde = { version = "1", optional = true }
+regex = { version = "1", optional = true }
+log = "0.4"
+
+[target.'cfg(unix)'.dependencies.nix]
+version = "0.27"
+optional = true
+
+[features]
+default = ["std"]
+std = []
+json = ["dep:regex"]
+derive = ["serde/derive"]
+"#;
+
+#[test]
+fn features_are_read_in_order_without_default() {
+    assert_eq!(
+        feature_labels(EXAMPLE_MANIFEST, FeatureOptions::default()),
+        Ok(["std", "json", "derive"].map(str::to_owned).to_vec())
+    );
+}
+
+#[test]
+fn optional_dependencies_are_included_unless_only_enabled_with_dep() {
+    assert_eq!(
+        feature_labels(EXAMPLE_MANIFEST, FeatureOptions {
+            optional_dependencies: true,
+        }),
+        Ok(["std", "json", "derive", "serde", "nix"].map(str::to_owned).to_vec())
+    );
+}
+
+#[test]
+fn manifests_without_features_are_reported() {
+    assert_eq!(
+        feature_labels("[package]\nname = \"a\"\n", FeatureOptions::default()),
+        Err(Error::NoFeatures)
+    );
+    assert!(matches!(
+        feature_labels("[features\n", FeatureOptions::default()),
+        Err(Error::Malformed(_))
+    ));
+}
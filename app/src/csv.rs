@@ -0,0 +1,315 @@
+//! Reading and writing the statuses of a board as CSV, for use with
+//! spreadsheets.
+//!
+//! The first row holds the column labels, after an empty corner cell. Each row
+//! after that starts with a row label, followed by the status of each tile in
+//! that row, from left to right:
+//!
+//! ```text
+//! ,a,b,c
+//! x,passes,unchecked,fails
+//! y,blocked,not-applicable,needs-recheck
+//! ```
+//!
+//! Statuses are written with the names from `Status::name`. When reading,
+//! upper and lower case are treated the same, and an empty cell means
+//! unchecked. The corner cell is ignored.
+//!
+//! Cells are separated by commas, and rows by line breaks. A cell with a
+//! comma, a double quote or a line break in it is wrapped in double quotes,
+//! with each double quote inside it doubled, as described in RFC 4180.
+//!
+//! Only the labels and the statuses are included. Notes, the eye and the
+//! symmetry of the board are left out, so a board read from CSV is
+//! asymmetric, and has no notes.
+
+use crate::{Axis, Board, Label, LabelCountError, Status};
+
+/// One-based, counting the header row, to match what spreadsheets show.
+pub type RowNumber = usize;
+/// One-based, counting the column of row labels, to match what spreadsheets
+/// show.
+pub type ColumnNumber = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// A cell was opened with a double quote, but never closed.
+    UnclosedQuote { row: RowNumber },
+    /// A double quote in a cell that did not start with one, or text after
+    /// the closing double quote of a cell.
+    StrayQuote { row: RowNumber, column: ColumnNumber },
+    /// A row does not have one cell per column label, plus its row label.
+    Ragged {
+        row: RowNumber,
+        row_label: Label,
+        expected: usize,
+        found: usize,
+    },
+    UnknownStatus {
+        row: RowNumber,
+        column: ColumnNumber,
+        row_label: Label,
+        column_label: Label,
+        value: String,
+    },
+    /// There were too many, or too few, column labels or rows.
+    LabelCount(LabelCountError),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use Error::*;
+        match self {
+            UnclosedQuote { row } => write!(
+                f,
+                "row {row}: a quoted cell is never closed"
+            ),
+            StrayQuote { row, column } => write!(
+                f,
+                "row {row}, column {column}: unexpected double quote"
+            ),
+            Ragged { row, row_label, expected, found } => write!(
+                f,
+                "row {row} (\"{row_label}\"): expected {expected} cells, to match the header, but found {found}"
+            ),
+            UnknownStatus { row, column, row_label, column_label, value } => write!(
+                f,
+                "row {row} (\"{row_label}\"), column {column} (\"{column_label}\"): unknown status \"{value}\""
+            ),
+            LabelCount(e) => e.fmt(f),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+fn push_cell(output: &mut String, cell: &str) {
+    if cell.contains([',', '"', '\n', '\r']) {
+        output.push('"');
+        output.push_str(&cell.replace('"', "\"\""));
+        output.push('"');
+    } else {
+        output.push_str(cell);
+    }
+}
+
+pub fn write(board: &Board) -> String {
+    let mut output = String::with_capacity(1024);
+
+    for label in board.labels(Axis::Columns) {
+        output.push(',');
+        push_cell(&mut output, label);
+    }
+    output.push('\n');
+
+    let column_count = board.labels(Axis::Columns).len();
+
+    for (row, label) in board.labels(Axis::Rows).iter().enumerate() {
+        push_cell(&mut output, label);
+
+        for column in 0..column_count {
+            output.push(',');
+            output.push_str(
+                board.status(column, row).unwrap_or_default().name()
+            );
+        }
+        output.push('\n');
+    }
+
+    output
+}
+
+/// Splits `text` into rows of cells, undoing the quoting.
+fn split_rows(text: &str) -> Result<Vec<Vec<String>>, Error> {
+    let mut rows = Vec::new();
+    let mut cells = Vec::new();
+    let mut cell = String::new();
+
+    let mut chars = text.chars().peekable();
+
+    loop {
+        let row = rows.len() + 1;
+        let column = cells.len() + 1;
+
+        let ended_by = if chars.peek() == Some(&'"') {
+            chars.next();
+
+            loop {
+                match chars.next() {
+                    Some('"') if chars.peek() == Some(&'"') => {
+                        chars.next();
+                        cell.push('"');
+                    },
+                    Some('"') => break,
+                    Some(c) => cell.push(c),
+                    None => return Err(Error::UnclosedQuote { row }),
+                }
+            }
+
+            match chars.next() {
+                Some('"') => unreachable!("doubled quotes are handled above"),
+                c @ (Some(',' | '\n' | '\r') | None) => c,
+                Some(_) => return Err(Error::StrayQuote { row, column }),
+            }
+        } else {
+            loop {
+                match chars.next() {
+                    c @ (Some(',' | '\n' | '\r') | None) => break c,
+                    Some('"') => return Err(Error::StrayQuote { row, column }),
+                    Some(c) => cell.push(c),
+                }
+            }
+        };
+
+        cells.push(core::mem::take(&mut cell));
+
+        match ended_by {
+            Some(',') => {},
+            Some(c) => {
+                if c == '\r' && chars.peek() == Some(&'\n') {
+                    chars.next();
+                }
+                rows.push(core::mem::take(&mut cells));
+            },
+            None => {
+                rows.push(cells);
+                break;
+            },
+        }
+    }
+
+    // Drop the empty rows that a trailing line break, or a few, leave.
+    while rows.last().is_some_and(|cells| matches!(cells.as_slice(), [c] if c.is_empty())) {
+        rows.pop();
+    }
+
+    Ok(rows)
+}
+
+pub fn read(text: &str) -> Result<Board, Error> {
+    // Some spreadsheets start their CSV files with a byte order mark.
+    let text = text.strip_prefix('\u{feff}').unwrap_or(text);
+
+    let mut rows = split_rows(text)?.into_iter();
+
+    let column_labels: Vec<Label> = rows.next()
+        .unwrap_or_default()
+        .into_iter()
+        .skip(1)
+        .collect();
+
+    let (row_labels, row_cells): (Vec<Label>, Vec<Vec<String>>) = rows
+        .map(|mut cells| {
+            let label = cells.remove(0);
+            (label, cells)
+        })
+        .unzip();
+
+    let mut board = Board::with_axis_labels(column_labels, row_labels)
+        .map_err(Error::LabelCount)?;
+
+    for (row, cells) in row_cells.into_iter().enumerate() {
+        let row_number = row + 2;
+        let row_label = |board: &Board| board.labels(Axis::Rows)[row].clone();
+
+        let expected = board.labels(Axis::Columns).len();
+        if cells.len() != expected {
+            return Err(Error::Ragged {
+                row: row_number,
+                row_label: row_label(&board),
+                // Counting the row label, like the spreadsheet would.
+                expected: expected + 1,
+                found: cells.len() + 1,
+            });
+        }
+
+        for (column, cell) in cells.into_iter().enumerate() {
+            let name = cell.trim();
+            let status = if name.is_empty() {
+                Some(Status::Unchecked)
+            } else {
+                Status::from_name(&name.to_ascii_lowercase())
+            };
+
+            let Some(status) = status else {
+                return Err(Error::UnknownStatus {
+                    row: row_number,
+                    column: column + 2,
+                    row_label: row_label(&board),
+                    column_label: board.labels(Axis::Columns)[column].clone(),
+                    value: cell,
+                });
+            };
+
+            // The board was made with this many columns and rows, so this is
+            // in bounds.
+            let _ = board.set_status(column, row, status);
+        }
+    }
+
+    Ok(board)
+}
+
+#[test]
+fn boards_round_trip_through_csv() {
+    let mut board = Board::with_axis_labels(
+        ["plain", "with, comma", "with \"quotes\"", "with\nnewline"]
+            .map(str::to_owned).to_vec(),
+        ["x", "", "y"].map(str::to_owned).to_vec(),
+    ).unwrap();
+    for (i, &status) in Status::ALL.iter().enumerate() {
+        board.set_status(i % 4, i / 4, status).unwrap();
+    }
+
+    let read_board = read(&write(&board)).unwrap();
+
+    assert_eq!(read_board.labels(Axis::Columns), board.labels(Axis::Columns));
+    assert_eq!(read_board.labels(Axis::Rows), board.labels(Axis::Rows));
+    assert_eq!(read_board.tiles.tiles, board.tiles.tiles);
+}
+
+#[test]
+fn spreadsheet_quirks_are_accepted() {
+    let board = read(
+        "\u{feff}corner,a,b,c\r\nx,Passes, ,\r\ny,,FAILS,\r\nz,,,\r\n\r\n"
+    ).unwrap();
+
+    assert_eq!(board.labels(Axis::Rows), ["x", "y", "z"]);
+    assert_eq!(board.status(0, 0), Ok(Status::Passes));
+    assert_eq!(board.status(1, 0), Ok(Status::Unchecked));
+    assert_eq!(board.status(1, 1), Ok(Status::Fails));
+}
+
+#[test]
+fn errors_name_the_row_and_column() {
+    assert_eq!(
+        read(",a,b,c\nx,passes\ny,,,\nz,,,\n").unwrap_err(),
+        Error::Ragged {
+            row: 2,
+            row_label: "x".to_owned(),
+            expected: 4,
+            found: 2,
+        }
+    );
+
+    assert_eq!(
+        read(",a,b,c\nx,passes,fails,\ny,fails,maybe,\nz,,,\n").unwrap_err(),
+        Error::UnknownStatus {
+            row: 3,
+            column: 3,
+            row_label: "y".to_owned(),
+            column_label: "b".to_owned(),
+            value: "maybe".to_owned(),
+        }
+    );
+
+    assert_eq!(
+        read(",a\n\"x,passes\n").unwrap_err(),
+        Error::UnclosedQuote { row: 2 }
+    );
+    assert_eq!(
+        read(",a\nx,pa\"sses\n").unwrap_err(),
+        Error::StrayQuote { row: 2, column: 2 }
+    );
+    assert!(matches!(read(",a\n"), Err(Error::LabelCount(_))));
+}
@@ -98,36 +98,35 @@ pub struct Sizes {
     pub draw_wh: DrawWH,
     pub play_xywh: PlayXYWH,
     pub board_xywh: BoardXYWH,
+    /// The side length of the tiles on the board, which shrink as the board
+    /// gets more of them.
     pub tile_side_length: TileSideLength,
+    /// The side length of the tiles the rest of the UI is laid out with. This
+    /// does not depend on the size of the board.
+    pub ui_tile_side_length: TileSideLength,
     pub text_box_margin: TextBoxMargin,
+    pub(crate) tile_wh: tile::WH,
 }
 
-use crate::tile;
+use crate::{tile, Axis};
 
-const LEFT_UI_WIDTH_TILES: TileCount = 9;
-const RIGHT_UI_WIDTH_TILES: TileCount = 9;
-const CENTER_UI_WIDTH_TILES: TileCount = if tile::X::COUNT < tile::Y::COUNT {
-    tile::X::COUNT as TileCount
-} else {
-    tile::Y::COUNT as TileCount
-};
-
-pub(crate) const BOARD_W_TILES: TileCount = tile::X::COUNT as TileCount;
-pub(crate) const BOARD_H_TILES: TileCount = tile::Y::COUNT as TileCount;
+pub(crate) const LEFT_UI_WIDTH_TILES: TileCount = 9;
+pub(crate) const RIGHT_UI_WIDTH_TILES: TileCount = 9;
+/// The board is scaled to fit within this many UI tiles, (plus the labels.)
+pub(crate) const CENTER_UI_WIDTH_TILES: TileCount = 16;
 
 const TOP_LABELS_HEIGHT_TILES: TileCount = 1;
 const CENTER_UI_HEIGHT_TILES: TileCount = CENTER_UI_WIDTH_TILES + TOP_LABELS_HEIGHT_TILES;
-const NON_BOARD_H_TILES: TileCount = CENTER_UI_HEIGHT_TILES - BOARD_H_TILES;
 
 const DRAW_WIDTH_TILES: TileCount = LEFT_UI_WIDTH_TILES
     + CENTER_UI_WIDTH_TILES
     + RIGHT_UI_WIDTH_TILES;
 
-pub fn fresh_sizes(wh: DrawWH) -> Sizes {
+pub fn fresh_sizes(wh: DrawWH, tile_wh: tile::WH) -> Sizes {
     let w_length_bound = wh.w / DRAW_WIDTH_TILES as DrawW;
     let h_length_bound = wh.h / CENTER_UI_HEIGHT_TILES as DrawH;
 
-    let (raw_bound, tile_side_length, board_x_offset, mut board_y_offset) = {
+    let (raw_bound, ui_tile_side_length, board_x_offset, mut board_y_offset) = {
         if (w_length_bound - h_length_bound).abs() < 0.5 {
             (h_length_bound, h_length_bound.trunc(), h_length_bound.fract() / 2., h_length_bound.fract() / 2.)
         } else if w_length_bound > h_length_bound {
@@ -141,17 +140,32 @@ pub fn fresh_sizes(wh: DrawWH) -> Sizes {
         }
     };
 
-    board_y_offset += (NON_BOARD_H_TILES as DrawLength * tile_side_length) / 2.;
+    let board_w_tiles = tile_wh.w() as TileCount;
+    let board_h_tiles = tile_wh.h() as TileCount;
+
+    // The board tiles are the largest whole number of pixels that lets the
+    // board and the top labels fit in the center of the UI. The left labels go
+    // in the left UI area.
+    let tile_side_length = DrawLength::min(
+        (CENTER_UI_WIDTH_TILES as DrawLength * ui_tile_side_length)
+            / board_w_tiles as DrawLength,
+        (CENTER_UI_HEIGHT_TILES as DrawLength * ui_tile_side_length)
+            / (board_h_tiles + TOP_LABELS_HEIGHT_TILES) as DrawLength,
+    ).trunc().max(1.);
+
+    board_y_offset += (TOP_LABELS_HEIGHT_TILES as DrawLength * tile_side_length) / 2.;
 
     let play_area_w = raw_bound * DRAW_WIDTH_TILES as PlayW;
     let play_area_h = raw_bound * CENTER_UI_HEIGHT_TILES as PlayH;
     let play_area_x = (wh.w - play_area_w) / 2.;
     let play_area_y = (wh.h - play_area_h) / 2.;
 
-    let board_area_w = tile_side_length * BOARD_W_TILES as BoardW;
-    let board_area_h = tile_side_length * BOARD_H_TILES as BoardH;
-    let board_area_x = play_area_x + board_x_offset + (play_area_w - board_area_w) / 2.;
-    let board_area_y = play_area_y + board_y_offset + (play_area_h - board_area_h) / 2.;
+    let board_area_w = tile_side_length * board_w_tiles as BoardW;
+    let board_area_h = tile_side_length * board_h_tiles as BoardH;
+    // Rounded so that the tile positions are whole numbers too, and so they
+    // can be converted back and forth exactly.
+    let board_area_x = (play_area_x + board_x_offset + (play_area_w - board_area_w) / 2.).round();
+    let board_area_y = (play_area_y + board_y_offset + (play_area_h - board_area_h) / 2.).round();
 
     Sizes {
         draw_wh: wh,
@@ -168,7 +182,23 @@ pub fn fresh_sizes(wh: DrawWH) -> Sizes {
             h: board_area_h,
         },
         tile_side_length,
-        text_box_margin: 3. * tile_side_length / 32.,
+        ui_tile_side_length,
+        text_box_margin: 3. * ui_tile_side_length / 32.,
+        tile_wh,
+    }
+}
+
+pub(crate) fn tile_draw_wh(sizes: &Sizes) -> DrawWH {
+    DrawWH {
+        w: sizes.tile_side_length,
+        h: sizes.tile_side_length,
+    }
+}
+
+pub(crate) fn ui_tile_draw_wh(sizes: &Sizes) -> DrawWH {
+    DrawWH {
+        w: sizes.ui_tile_side_length,
+        h: sizes.ui_tile_side_length,
     }
 }
 
@@ -187,7 +217,7 @@ pub(crate) fn top_label_rect(sizes: &Sizes) -> Rect {
     Rect {
         min_x: zero_xy.x,
         min_y: zero_xy.y - label_wh.h,
-        max_x: zero_xy.x + crate::LABEL_COUNT as DrawX * label_wh.w,
+        max_x: zero_xy.x + sizes.tile_wh.w() as DrawX * label_wh.w,
         max_y: zero_xy.y,
     }
 }
@@ -198,28 +228,80 @@ pub(crate) fn left_label_rect(sizes: &Sizes) -> Rect {
     let zero_xy = draw_xy_from_tile(sizes, <_>::default());
 
     Rect {
-        min_x: zero_xy.x - label_wh.h,
+        min_x: zero_xy.x - label_wh.w,
         min_y: zero_xy.y,
-        max_x: zero_xy.x + label_wh.h,
-        max_y: zero_xy.y + crate::LABEL_COUNT as DrawY * label_wh.h,
+        max_x: zero_xy.x,
+        max_y: zero_xy.y + sizes.tile_wh.h() as DrawY * label_wh.h,
     }
 }
 
-pub(crate) fn draw_xy_from_tile(sizes: &Sizes, txy: tile::XY) -> DrawXY {
-    let w_frac = tile::Coord::from(txy.x) as DrawLength / BOARD_W_TILES as DrawLength;
-    let h_frac = tile::Coord::from(txy.y) as DrawLength / BOARD_H_TILES as DrawLength;
+/// The rect of the label with the given index.
+pub(crate) fn label_rect(sizes: &Sizes, axis: Axis, index: usize) -> Rect {
+    let label_wh = label_wh(sizes);
 
+    let (min_x, min_y) = match axis {
+        Axis::Columns => {
+            let strip = top_label_rect(sizes);
+            (strip.min_x + index as DrawX * label_wh.w, strip.min_y)
+        },
+        Axis::Rows => {
+            let strip = left_label_rect(sizes);
+            (strip.min_x, strip.min_y + index as DrawY * label_wh.h)
+        },
+    };
+
+    Rect {
+        min_x,
+        min_y,
+        max_x: min_x + label_wh.w,
+        max_y: min_y + label_wh.h,
+    }
+}
+
+/// The index of the label at the given point, if there is one there.
+pub(crate) fn label_index_from_draw(
+    sizes: &Sizes,
+    axis: Axis,
+    dxy: DrawXY,
+) -> Option<usize> {
+    let (strip, offset, count) = match axis {
+        Axis::Columns => {
+            let strip = top_label_rect(sizes);
+            let offset = dxy.x - strip.min_x;
+            (strip, offset, sizes.tile_wh.w())
+        },
+        Axis::Rows => {
+            let strip = left_label_rect(sizes);
+            let offset = dxy.y - strip.min_y;
+            (strip, offset, sizes.tile_wh.h())
+        },
+    };
+
+    if !strip.contains(dxy) {
+        return None;
+    }
+
+    let index = (offset / sizes.tile_side_length) as usize;
+
+    // In case of rounding right at the far edge.
+    Some(index.min(count as usize - 1))
+}
+
+pub(crate) fn draw_xy_from_tile(sizes: &Sizes, txy: tile::XY) -> DrawXY {
     DrawXY {
-        x: sizes.board_xywh.x + sizes.board_xywh.w * w_frac,
-        y: sizes.board_xywh.y + sizes.board_xywh.h * h_frac,
+        x: sizes.board_xywh.x
+            + tile::Coord::from(txy.x) as DrawLength * sizes.tile_side_length,
+        y: sizes.board_xywh.y
+            + tile::Coord::from(txy.y) as DrawLength * sizes.tile_side_length,
     }
 }
 
 pub(crate) fn tile_xy_from_draw(sizes: &Sizes, dxy: DrawXY) -> Option<tile::XY> {
-    tile::X::try_from(((dxy.x - sizes.board_xywh.x) / sizes.board_xywh.w) * tile::X::COUNT as DrawLength)
+    let wh = sizes.tile_wh;
+    tile::X::try_from_draw((dxy.x - sizes.board_xywh.x) / sizes.tile_side_length, wh)
         .ok()
         .and_then(|x| {
-            tile::Y::try_from(((dxy.y - sizes.board_xywh.y) / sizes.board_xywh.h) * tile::Y::COUNT as DrawLength)
+            tile::Y::try_from_draw((dxy.y - sizes.board_xywh.y) / sizes.tile_side_length, wh)
                 .ok()
                 .map(|y| tile::XY {
                     x,
@@ -228,35 +310,122 @@ pub(crate) fn tile_xy_from_draw(sizes: &Sizes, dxy: DrawXY) -> Option<tile::XY>
         })
 }
 
+#[cfg(test)]
+const EXAMPLE_TILE_WHS: [(tile::Count, tile::Count); 4] = [
+    (16, 16),
+    (3, 3),
+    (37, 37),
+    (tile::COUNT_MAX, tile::COUNT_MAX),
+];
+
 #[test]
 fn all_the_tile_xys_round_trip_through_draw_xy() {
-    let sizes = draw::fresh_sizes(EXAMPLE_WH);
+    for (w, h) in EXAMPLE_TILE_WHS {
+        let tile_wh = tile::WH::new(w, h).unwrap();
+        let sizes = fresh_sizes(EXAMPLE_WH, tile_wh);
 
-    for txy in tile::all_xys() {
-        let round_tripped = tile_xy_from_draw(
-            &sizes,
-            draw_xy_from_tile(&sizes, txy)
-        ).unwrap();
+        for txy in tile::all_xys(tile_wh) {
+            let round_tripped = tile_xy_from_draw(
+                &sizes,
+                draw_xy_from_tile(&sizes, txy)
+            ).unwrap();
 
-        assert_eq!(round_tripped, txy);
+            assert_eq!(round_tripped, txy);
+        }
     }
 }
 
 #[test]
 fn all_the_tile_xys_round_trip_through_draw_xy_when_offset_slightly() {
-    let sizes = draw::fresh_sizes(EXAMPLE_WH);
+    for (w, h) in EXAMPLE_TILE_WHS {
+        let tile_wh = tile::WH::new(w, h).unwrap();
+        let sizes = fresh_sizes(EXAMPLE_WH, tile_wh);
+
+        for txy in tile::all_xys(tile_wh) {
+            let mut draw_xy = draw_xy_from_tile(&sizes, txy);
+            draw_xy.x += sizes.tile_side_length / 8.;
+            draw_xy.y += sizes.tile_side_length / 8.;
+
+            let round_tripped = tile_xy_from_draw(
+                &sizes,
+                draw_xy,
+            ).unwrap();
+
+            assert_eq!(round_tripped, txy);
+        }
+    }
+}
+
+#[test]
+fn every_label_is_found_at_its_own_rect() {
+    for (w, h) in EXAMPLE_TILE_WHS {
+        let sizes = fresh_sizes(EXAMPLE_WH, tile::WH::new(w, h).unwrap());
+
+        for (axis, count) in [(Axis::Columns, w), (Axis::Rows, h)] {
+            for index in 0..count as usize {
+                let rect = label_rect(&sizes, axis, index);
+                let middle = DrawXY {
+                    x: (rect.min_x + rect.max_x) / 2.,
+                    y: (rect.min_y + rect.max_y) / 2.,
+                };
+
+                assert_eq!(
+                    label_index_from_draw(&sizes, axis, middle),
+                    Some(index),
+                    "{w}x{h} {axis:?}",
+                );
+            }
+        }
+    }
+}
+
+#[test]
+fn the_board_and_its_labels_fit_inside_the_play_area() {
+    for (w, h) in EXAMPLE_TILE_WHS {
+        let sizes = fresh_sizes(EXAMPLE_WH, tile::WH::new(w, h).unwrap());
+
+        let top = top_label_rect(&sizes);
+        let left = left_label_rect(&sizes);
+        let board = &sizes.board_xywh;
+        let play = &sizes.play_xywh;
+
+        assert!(left.min_x >= play.x, "{w}x{h}");
+        assert!(top.min_y >= play.y, "{w}x{h}");
+        assert!(board.x + board.w <= play.x + play.w, "{w}x{h}");
+        assert!(board.y + board.h <= play.y + play.h, "{w}x{h}");
+    }
+}
 
-    for txy in tile::all_xys() {
-        let mut draw_xy = draw_xy_from_tile(&sizes, txy);
-        draw_xy.x += sizes.tile_side_length / 8.;
-        draw_xy.y += sizes.tile_side_length / 8.;
+/// The top left corner of the area between the left and right UI areas, just
+/// below the top labels.
+pub(crate) fn center_ui_xy(sizes: &Sizes) -> DrawXY {
+    DrawXY {
+        x: sizes.play_xywh.x
+            + LEFT_UI_WIDTH_TILES as DrawLength * sizes.ui_tile_side_length,
+        y: sizes.play_xywh.y
+            + TOP_LABELS_HEIGHT_TILES as DrawLength * sizes.ui_tile_side_length,
+    }
+}
 
-        let round_tripped = tile_xy_from_draw(
-            &sizes,
-            draw_xy,
-        ).unwrap();
+/// The top left corner of the area to the left of the board, just below the
+/// top labels.
+pub(crate) fn left_ui_xy(sizes: &Sizes) -> DrawXY {
+    DrawXY {
+        x: sizes.play_xywh.x,
+        y: sizes.play_xywh.y
+            + TOP_LABELS_HEIGHT_TILES as DrawLength * sizes.ui_tile_side_length,
+    }
+}
 
-        assert_eq!(round_tripped, txy);
+/// The top left corner of the area to the right of the board, just below the
+/// top labels.
+pub(crate) fn right_ui_xy(sizes: &Sizes) -> DrawXY {
+    DrawXY {
+        x: sizes.play_xywh.x
+            + (LEFT_UI_WIDTH_TILES + CENTER_UI_WIDTH_TILES) as DrawLength
+                * sizes.ui_tile_side_length,
+        y: sizes.play_xywh.y
+            + TOP_LABELS_HEIGHT_TILES as DrawLength * sizes.ui_tile_side_length,
     }
 }
 
@@ -267,7 +436,7 @@ pub(crate) fn zero_tile_xy(sizes: &Sizes) -> DrawXY {
 #[cfg(test)]
 const EXAMPLE_WH: DrawWH = DrawWH { w: 1366., h: 768. };
 
-use crate::{cell::UiState, ArrowKind, Dir, LRThreeSlice, NineSlice, BorderKind};
+use crate::{cell::UiState, ArrowKind, Dir, LRThreeSlice, NineSlice, BorderKind, Status};
 
 #[derive(Clone, Copy, Debug, PartialEq, Eq)]
 pub enum SpriteKind {
@@ -281,9 +450,15 @@ pub enum SpriteKind {
     ClosedEye,
     HalfLidEye,
     Unchecked(UiState),
-    Checked(UiState),
+    Passes(UiState),
+    Fails(UiState),
+    Blocked(UiState),
+    NeedsRecheck(UiState),
+    NotApplicable(UiState),
     LRThreeSlice(LRThreeSlice, BorderKind),
     NineSlice(NineSlice, BorderKind),
+    /// Drawn on top of tiles that have a note.
+    NoteMarker,
 }
 
 impl Default for SpriteKind {
@@ -296,12 +471,72 @@ impl Default for SpriteKind {
 pub enum Command {
     Sprite(SpriteSpec),
     Text(TextSpec),
+    Overlay(OverlaySpec),
+    Bar(BarSpec),
 }
 
 #[derive(Debug)]
 pub struct SpriteSpec {
     pub sprite: SpriteKind,
     pub xy: DrawXY,
+    pub wh: DrawWH,
+}
+
+/// The platform layer decides what each kind looks like, for example which
+/// colour to draw it with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OverlayKind {
+    /// Makes whatever is underneath look less prominent.
+    Dim,
+    /// Marks what keyboard input will act on. Should leave the middle of the
+    /// rectangle visible.
+    Focus,
+    /// Marks what is part of the current selection. Should leave whatever
+    /// is underneath recognizable.
+    Selected,
+    /// Marks the tiles in the same row or column as the hovered one. Should
+    /// be fainter than `Selected`.
+    Crosshair,
+}
+
+/// A rectangle drawn on top of whatever was drawn before it.
+#[derive(Debug)]
+pub struct OverlaySpec {
+    pub kind: OverlayKind,
+    pub xy: DrawXY,
+    pub wh: DrawWH,
+}
+
+/// The platform layer decides what colour to fill each kind of bar with.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BarKind {
+    /// How much of something is checked.
+    Checked,
+    /// How much of something has the given status.
+    Status(Status),
+}
+
+/// A horizontal bar, filled from the left for `fraction` of its width, with
+/// the rest left empty.
+#[derive(Debug)]
+pub struct BarSpec {
+    pub kind: BarKind,
+    pub xy: DrawXY,
+    pub wh: DrawWH,
+    /// From 0 to 1.
+    pub fraction: f32,
+}
+
+/// Where to draw the caret, and what to show as selected, in a
+/// `TextKind::TextBoxWithCursor`. The indexes are in bytes into the text.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct CursorSpec {
+    pub caret: usize,
+    /// The other end of the selection. The same as `caret` if nothing is
+    /// selected.
+    pub anchor: usize,
+    /// False on the frames where the caret is blinked off.
+    pub is_caret_shown: bool,
 }
 
 /// This is provided to make font selection etc. easier for platform layers.
@@ -310,7 +545,7 @@ pub enum TextKind {
     UI,
     OneTile,
     TextBox,
-    TextBoxWithCursor,
+    TextBoxWithCursor(CursorSpec),
     CellLabel,
 }
 
@@ -0,0 +1,671 @@
+//! The on-disk format for boards.
+//!
+//! A board file is UTF-8 text, split into lines. The first line is a header
+//! made of a magic string and the version of the format, separated by a space:
+//!
+//! ```text
+//! interaction-checklist-board 1
+//! ```
+//!
+//! The rest of the file is a sequence of sections. Each section starts with a
+//! line holding the section's name and the number of lines in its body,
+//! separated by a space, and the body lines follow directly after that:
+//!
+//! ```text
+//! column-labels 16
+//! first label
+//! second label
+//! ...
+//! row-labels 4
+//! ...
+//! tiles 4
+//! x.....x.........
+//! ................
+//! ...
+//! eye 1
+//! 3 2
+//! ```
+//!
+//! The sections are:
+//!
+//! * `column-labels`: One label per line, for the columns from left to right.
+//!   Backslashes, line feeds and carriage returns in a label are written as
+//!   `\\`, `\n` and `\r` respectively.
+//! * `row-labels`: The same as `column-labels`, but for the rows from top to
+//!   bottom.
+//! * `tiles`: One line per row of tiles, from top to bottom, with one
+//!   character per tile, from left to right. The characters are `.` for
+//!   unchecked, `x` for passes, `f` for fails, `b` for blocked, `?` for needs
+//!   recheck and `-` for not applicable. There must be as many rows as row
+//!   labels, and as many tiles in each row as there are column labels.
+//! * `eye`: A single line with the tile x and y coordinates of the eye,
+//!   separated by a space.
+//! * `symmetry`: A single line, either `dimmed` or `hidden`, saying the board
+//!   is symmetric and how the mirror tiles are shown. Boards without this
+//!   section are asymmetric. The tiles of symmetric boards are written out in
+//!   full, so readers that skip this section still see the same checks.
+//! * `triangle`: A single line, either `upper` or `lower`, saying which
+//!   triangle of a symmetric board is kept. Defaults to `lower`.
+//! * `diagonal`: A single line, one of `included`, `excluded` or
+//!   `not-applicable`, saying how the diagonal of a symmetric board is handled.
+//!   Defaults to `included`.
+//! * `notes`: One line per tile with a note, holding the tile x and y
+//!   coordinates and then the note, all separated by spaces. Notes are
+//!   escaped the same way as labels.
+//!
+//! `triangle` and `diagonal` are ignored on asymmetric boards.
+//!
+//! `column-labels`, `row-labels` and `tiles` are required. The sections may
+//! appear in any order.
+//!
+//! # Versions
+//!
+//! Readers skip over sections with names they do not recognize. So a section
+//! that older readers can safely ignore can be added without changing the
+//! version. Any other change bumps `VERSION`, and readers refuse to load files
+//! with a version greater than the one they know about, rather than guessing
+//! at what the file means.
+//!
+//! ## Version history
+//!
+//! * 1: The format described above.
+
+use crate::{
+    cell,
+    tile,
+    Board,
+    Diagonal,
+    Label,
+    MirrorTiles,
+    Symmetry,
+    SymmetricLayout,
+    Triangle,
+};
+
+pub type Version = u32;
+
+/// The latest version of the format. This is what `write` produces.
+pub const VERSION: Version = 1;
+
+const MAGIC: &str = "interaction-checklist-board";
+
+/// One-based, to match what text editors show.
+pub type LineNumber = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The file did not start with the expected header line.
+    NotABoardFile,
+    /// The file is from a newer version of the format than we understand.
+    UnsupportedVersion(Version),
+    /// The file ended before all the promised lines of a section were read.
+    Truncated { section: String, line: LineNumber },
+    Malformed { line: LineNumber, reason: &'static str },
+    MissingSection(&'static str),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        use Error::*;
+        match self {
+            NotABoardFile => write!(
+                f,
+                "not a board file: expected a first line starting with \"{MAGIC}\""
+            ),
+            UnsupportedVersion(version) => write!(
+                f,
+                "board file version {version} is newer than the latest supported version, {VERSION}"
+            ),
+            Truncated { section, line } => write!(
+                f,
+                "line {line}: file ends in the middle of the \"{section}\" section"
+            ),
+            Malformed { line, reason } => write!(f, "line {line}: {reason}"),
+            MissingSection(name) => write!(f, "missing \"{name}\" section"),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+const DIMMED: &str = "dimmed";
+const HIDDEN: &str = "hidden";
+
+const UPPER: &str = "upper";
+const LOWER: &str = "lower";
+
+const INCLUDED: &str = "included";
+const EXCLUDED: &str = "excluded";
+const NOT_APPLICABLE: &str = "not-applicable";
+
+fn status_char(status: cell::Status) -> char {
+    use cell::Status::*;
+    match status {
+        Unchecked => '.',
+        Passes => 'x',
+        Fails => 'f',
+        Blocked => 'b',
+        NeedsRecheck => '?',
+        NotApplicable => '-',
+    }
+}
+
+fn char_status(c: char) -> Option<cell::Status> {
+    cell::Status::ALL.iter().copied().find(|&status| status_char(status) == c)
+}
+
+fn escape(label: &str) -> String {
+    let mut output = String::with_capacity(label.len());
+
+    for c in label.chars() {
+        match c {
+            '\\' => output.push_str("\\\\"),
+            '\n' => output.push_str("\\n"),
+            '\r' => output.push_str("\\r"),
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+fn unescape(line: &str) -> Option<Label> {
+    let mut output = String::with_capacity(line.len());
+
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        if c == '\\' {
+            match chars.next()? {
+                '\\' => output.push('\\'),
+                'n' => output.push('\n'),
+                'r' => output.push('\r'),
+                _ => return None,
+            }
+        } else {
+            output.push(c);
+        }
+    }
+
+    Some(output)
+}
+
+pub fn write(board: &Board) -> String {
+    use core::fmt::Write;
+
+    let mut output = String::with_capacity(1024);
+
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(output, "{MAGIC} {VERSION}");
+
+    for (name, labels) in [
+        ("column-labels", &board.column_labels),
+        ("row-labels", &board.row_labels),
+    ] {
+        let _ = writeln!(output, "{name} {}", labels.len());
+        for label in labels.iter() {
+            let _ = writeln!(output, "{}", escape(label));
+        }
+    }
+
+    let wh = board.tiles.wh;
+    let _ = writeln!(output, "tiles {}", wh.h());
+    for row in board.tiles.tiles.chunks(wh.w() as usize) {
+        for tile_data in row {
+            output.push(status_char(tile_data.status));
+        }
+        output.push('\n');
+    }
+
+    let eye_xy = board.eye.xy;
+    let _ = writeln!(output, "eye 1");
+    let _ = writeln!(
+        output,
+        "{} {}",
+        tile::Coord::from(eye_xy.x),
+        tile::Coord::from(eye_xy.y),
+    );
+
+    let notes: Vec<_> = board.tiles.tiles.iter()
+        .enumerate()
+        .filter(|(_, tile_data)| !tile_data.note.is_empty())
+        .collect();
+    if !notes.is_empty() {
+        let _ = writeln!(output, "notes {}", notes.len());
+        for (i, tile_data) in notes {
+            let xy = tile::i_to_xy(i, wh);
+            let _ = writeln!(
+                output,
+                "{} {} {}",
+                tile::Coord::from(xy.x),
+                tile::Coord::from(xy.y),
+                escape(&tile_data.note),
+            );
+        }
+    }
+
+    if let Symmetry::Symmetric(layout) = board.symmetry {
+        for (name, value) in [
+            (
+                "symmetry",
+                match layout.mirror_tiles {
+                    MirrorTiles::Dimmed => DIMMED,
+                    MirrorTiles::Hidden => HIDDEN,
+                },
+            ),
+            (
+                "triangle",
+                match layout.kept {
+                    Triangle::Upper => UPPER,
+                    Triangle::Lower => LOWER,
+                },
+            ),
+            (
+                "diagonal",
+                match layout.diagonal {
+                    Diagonal::Included => INCLUDED,
+                    Diagonal::Excluded => EXCLUDED,
+                    Diagonal::NotApplicable => NOT_APPLICABLE,
+                },
+            ),
+        ] {
+            let _ = writeln!(output, "{name} 1\n{value}");
+        }
+    }
+
+    output
+}
+
+struct Section<'text> {
+    name: &'text str,
+    /// The line number of the first line of `body`, or where it would be if
+    /// `body` is empty.
+    first_line: LineNumber,
+    body: Vec<&'text str>,
+}
+
+pub fn read(text: &str) -> Result<Board, Error> {
+    let mut lines = text.lines().enumerate().map(|(i, l)| (i + 1, l));
+
+    let version = match lines.next() {
+        Some((_, header)) => {
+            let mut parts = header.split(' ');
+            if parts.next() != Some(MAGIC) {
+                return Err(Error::NotABoardFile);
+            }
+
+            parts.next()
+                .and_then(|v| v.parse::<Version>().ok())
+                .ok_or(Error::Malformed {
+                    line: 1,
+                    reason: "expected a version number after the magic string",
+                })?
+        },
+        None => return Err(Error::NotABoardFile),
+    };
+
+    if version == 0 {
+        return Err(Error::Malformed {
+            line: 1,
+            reason: "there is no version 0 of the format",
+        });
+    }
+    if version > VERSION {
+        return Err(Error::UnsupportedVersion(version));
+    }
+
+    let mut column_labels = None;
+    let mut row_labels = None;
+    let mut tiles = None;
+    let mut eye_xy = None;
+    let mut notes = None;
+    let mut mirror_tiles = None;
+    let mut kept = None;
+    let mut diagonal = None;
+
+    while let Some((header_line, header)) = lines.next() {
+        let (name, count) = header.split_once(' ')
+            .and_then(|(name, count)| {
+                count.parse::<usize>().ok().map(|count| (name, count))
+            })
+            .ok_or(Error::Malformed {
+                line: header_line,
+                reason: "expected a section name and a line count",
+            })?;
+
+        // Not using `with_capacity` since `count` came from the file, and
+        // may be wildly wrong.
+        let mut body = Vec::new();
+        for _ in 0..count {
+            match lines.next() {
+                Some((_, line)) => body.push(line),
+                None => return Err(Error::Truncated {
+                    section: name.to_string(),
+                    line: header_line,
+                }),
+            }
+        }
+
+        let section = Section {
+            name,
+            first_line: header_line + 1,
+            body,
+        };
+
+        match section.name {
+            "column-labels" => { column_labels = Some(read_labels(&section)?); },
+            "row-labels" => { row_labels = Some(read_labels(&section)?); },
+            "tiles" => { tiles = Some((read_tiles(&section)?, header_line)); },
+            "eye" => { eye_xy = Some((read_eye(&section)?, header_line)); },
+            "notes" => { notes = Some(read_notes(&section)?); },
+            "symmetry" => {
+                mirror_tiles = Some((
+                    read_word(&section, &[
+                        (DIMMED, MirrorTiles::Dimmed),
+                        (HIDDEN, MirrorTiles::Hidden),
+                    ])?,
+                    header_line
+                ));
+            },
+            "triangle" => {
+                kept = Some(read_word(&section, &[
+                    (UPPER, Triangle::Upper),
+                    (LOWER, Triangle::Lower),
+                ])?);
+            },
+            "diagonal" => {
+                diagonal = Some(read_word(&section, &[
+                    (INCLUDED, Diagonal::Included),
+                    (EXCLUDED, Diagonal::Excluded),
+                    (NOT_APPLICABLE, Diagonal::NotApplicable),
+                ])?);
+            },
+            // Skip unknown sections, as described in the module docs.
+            _ => {},
+        }
+    }
+
+    let column_labels = column_labels.ok_or(Error::MissingSection("column-labels"))?;
+    let row_labels = row_labels.ok_or(Error::MissingSection("row-labels"))?;
+    let (tiles, tiles_line) = tiles.ok_or(Error::MissingSection("tiles"))?;
+
+    let wh = tiles.wh;
+    if wh.w() as usize != column_labels.len() {
+        return Err(Error::Malformed {
+            line: tiles_line,
+            reason: "there must be one column of tiles per column label",
+        });
+    }
+    if wh.h() as usize != row_labels.len() {
+        return Err(Error::Malformed {
+            line: tiles_line,
+            reason: "there must be one row of tiles per row label",
+        });
+    }
+
+    let mut tiles = tiles;
+    for (x, y, note, line) in notes.unwrap_or_default() {
+        let xy = tile::X::try_from_coord(x, wh).ok()
+            .zip(tile::Y::try_from_coord(y, wh).ok())
+            .map(|(x, y)| tile::XY { x, y })
+            .ok_or(Error::Malformed {
+                line,
+                reason: "the note is for a tile outside the board",
+            })?;
+
+        tiles.tiles[tile::xy_to_i(xy, wh)].note = note;
+    }
+
+    let mut eye = crate::Eye::default();
+    if let Some(((x, y), eye_line)) = eye_xy {
+        eye.xy = tile::X::try_from_coord(x, wh).ok()
+            .zip(tile::Y::try_from_coord(y, wh).ok())
+            .map(|(x, y)| tile::XY { x, y })
+            .ok_or(Error::Malformed {
+                line: eye_line + 1,
+                reason: "the eye is outside the board",
+            })?;
+    }
+
+    let mut board = Board {
+        column_labels,
+        row_labels,
+        tiles,
+        eye,
+        symmetry: Symmetry::Asymmetric,
+    };
+
+    if let Some((mirror_tiles, symmetry_line)) = mirror_tiles {
+        let default = SymmetricLayout::default();
+        let layout = SymmetricLayout {
+            kept: kept.unwrap_or(default.kept),
+            mirror_tiles,
+            diagonal: diagonal.unwrap_or(default.diagonal),
+        };
+
+        board.set_symmetry(Symmetry::Symmetric(layout)).map_err(|_| Error::Malformed {
+            line: symmetry_line,
+            reason: "only square boards can be symmetric",
+        })?;
+    }
+
+    Ok(board)
+}
+
+fn read_labels(section: &Section) -> Result<Vec<Label>, Error> {
+    if !(crate::LABEL_COUNT_MIN..=crate::LABEL_COUNT_MAX)
+        .contains(&section.body.len()) {
+        return Err(Error::Malformed {
+            line: section.first_line - 1,
+            reason: "too many or too few labels",
+        });
+    }
+
+    let mut labels = Vec::with_capacity(section.body.len());
+    for (i, line) in section.body.iter().enumerate() {
+        labels.push(unescape(line).ok_or(Error::Malformed {
+            line: section.first_line + i,
+            reason: "invalid escape sequence in label",
+        })?);
+    }
+
+    Ok(labels)
+}
+
+fn read_tiles(section: &Section) -> Result<crate::Tiles, Error> {
+    let wrong_size = Error::Malformed {
+        line: section.first_line - 1,
+        reason: "too many or too few rows of tiles",
+    };
+
+    let h = tile::Count::try_from(section.body.len())
+        .map_err(|_| wrong_size.clone())?;
+    let w = match section.body.first() {
+        Some(line) => tile::Count::try_from(line.chars().count())
+            .map_err(|_| wrong_size.clone())?,
+        None => return Err(wrong_size),
+    };
+
+    let wh = tile::WH::new(w, h).map_err(|_| wrong_size)?;
+
+    let mut tiles = crate::Tiles::new(wh);
+    for (y, line) in section.body.iter().enumerate() {
+        let line_number = section.first_line + y;
+
+        if line.chars().count() != w as usize {
+            return Err(Error::Malformed {
+                line: line_number,
+                reason: "all rows of tiles must be the same length",
+            });
+        }
+
+        for (x, c) in line.chars().enumerate() {
+            tiles.tiles[tile::xy_to_i_usize((x, y), wh)].status = char_status(c)
+                .ok_or(Error::Malformed {
+                    line: line_number,
+                    reason: "unknown tile status character",
+                })?;
+        }
+    }
+
+    Ok(tiles)
+}
+
+fn read_eye(section: &Section) -> Result<(tile::Coord, tile::Coord), Error> {
+    let malformed = Error::Malformed {
+        line: section.first_line,
+        reason: "expected two tile coordinates",
+    };
+
+    let line = match section.body.as_slice() {
+        [line] => line,
+        _ => return Err(malformed),
+    };
+
+    line.split_once(' ')
+        .and_then(|(x, y)| x.parse().ok().zip(y.parse().ok()))
+        .ok_or(malformed)
+}
+
+/// Each note along with its tile coordinates and the line it was on.
+fn read_notes(
+    section: &Section
+) -> Result<Vec<(tile::Coord, tile::Coord, Label, LineNumber)>, Error> {
+    let mut notes = Vec::with_capacity(section.body.len());
+
+    for (i, line) in section.body.iter().enumerate() {
+        let line_number = section.first_line + i;
+
+        let mut parts = line.splitn(3, ' ');
+        let (x, y, note) = match (parts.next(), parts.next(), parts.next()) {
+            (Some(x), Some(y), Some(note)) => (x, y, note),
+            _ => return Err(Error::Malformed {
+                line: line_number,
+                reason: "expected two tile coordinates and a note",
+            }),
+        };
+
+        let (x, y) = x.parse().ok().zip(y.parse().ok())
+            .ok_or(Error::Malformed {
+                line: line_number,
+                reason: "expected two tile coordinates and a note",
+            })?;
+
+        let note = unescape(note).ok_or(Error::Malformed {
+            line: line_number,
+            reason: "invalid escape sequence in note",
+        })?;
+
+        notes.push((x, y, note, line_number));
+    }
+
+    Ok(notes)
+}
+
+/// Reads a section made of a single line, which must be one of the given
+/// words.
+fn read_word<A: Copy>(section: &Section, words: &[(&str, A)]) -> Result<A, Error> {
+    let malformed = Error::Malformed {
+        line: section.first_line,
+        reason: "unknown value for this section",
+    };
+
+    let line = match section.body.as_slice() {
+        [line] => line,
+        _ => return Err(malformed),
+    };
+
+    words.iter()
+        .find(|(word, _)| word == line)
+        .map(|&(_, value)| value)
+        .ok_or(malformed)
+}
+
+#[cfg(test)]
+fn example_board() -> Board {
+    let mut board = Board::with_axis_labels(
+        (0..7).map(|i| format!("column {i}")).collect(),
+        (0..4).map(|i| format!("row {i}")).collect(),
+    ).unwrap();
+
+    board.column_labels[3] = "back\\slash and\nnewline".to_string();
+
+    for (i, tile_data) in board.tiles.tiles.iter_mut().enumerate() {
+        tile_data.status = cell::Status::ALL[i % cell::Status::ALL.len()];
+    }
+
+    board.tiles.tiles[5].note = "fails when\nthe \\ key is held".to_string();
+    board.tiles.tiles[9].note = "ok".to_string();
+
+    board.eye.xy = tile::i_to_xy(23, board.tiles.wh);
+
+    board
+}
+
+#[test]
+fn boards_round_trip_through_the_file_format() {
+    let board = example_board();
+
+    let read_board = read(&write(&board)).unwrap();
+
+    assert_eq!(read_board.column_labels, board.column_labels);
+    assert_eq!(read_board.row_labels, board.row_labels);
+    assert_eq!(read_board.tiles.tiles, board.tiles.tiles);
+    assert_eq!(read_board.eye.xy, board.eye.xy);
+}
+
+#[test]
+fn unknown_sections_are_skipped() {
+    let written = write(&example_board());
+
+    let (header, rest) = written.split_once('\n').unwrap();
+    let with_extra = format!("{header}\nfrom-the-future 2\nsome\ndata\n{rest}");
+
+    assert!(read(&with_extra).is_ok());
+}
+
+#[test]
+fn newer_versions_are_rejected() {
+    let written = write(&example_board());
+
+    let (_, rest) = written.split_once('\n').unwrap();
+    let newer = format!("{MAGIC} {}\n{rest}", VERSION + 1);
+
+    assert_eq!(read(&newer).unwrap_err(), Error::UnsupportedVersion(VERSION + 1));
+}
+
+#[test]
+fn version_0_is_rejected() {
+    let written = write(&example_board());
+
+    let (_, rest) = written.split_once('\n').unwrap();
+    let zero = format!("{MAGIC} 0\n{rest}");
+
+    assert!(matches!(read(&zero), Err(Error::Malformed { line: 1, .. })));
+}
+
+#[test]
+fn truncated_files_are_reported_as_such() {
+    let written = write(&example_board());
+
+    // Cut at a line boundary, so the last line read is not itself malformed.
+    let lines: Vec<_> = written.lines().collect();
+    let truncated = lines[..lines.len() / 2].join("\n");
+
+    assert!(matches!(read(&truncated), Err(Error::Truncated { .. })));
+}
+
+#[test]
+fn symmetric_boards_round_trip_through_the_file_format() {
+    let mut board = Board::with_labels(vec![Label::default(); 5]).unwrap();
+    board.set_symmetry(Symmetry::Symmetric(SymmetricLayout {
+        kept: Triangle::Upper,
+        mirror_tiles: MirrorTiles::Hidden,
+        diagonal: Diagonal::NotApplicable,
+    })).unwrap();
+    board.set_status(4, 1, cell::Status::Fails).unwrap();
+
+    let read_board = read(&write(&board)).unwrap();
+
+    assert_eq!(read_board.symmetry, board.symmetry);
+    assert_eq!(read_board.tiles.tiles, board.tiles.tiles);
+}
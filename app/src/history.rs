@@ -0,0 +1,543 @@
+//! Undo and redo for changes to a board.
+//!
+//! Each step stores only what changed, with the values from before and after
+//! the change, so a step that touched one tile stays small on large boards.
+//! Adding, removing or moving whole lines changes the size or order of the
+//! tiles, so those are stored as what was done instead. If lines were added
+//! or removed in some other way, only the result is known, so the whole board
+//! is stored, from before and after.
+
+use crate::{Axis, Board, Label, Line, Symmetry, TileData};
+
+/// How many steps are kept. Once there are this many, recording a new step
+/// forgets the oldest one.
+pub const UNDO_LIMIT: usize = 256;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct TileChange {
+    index: usize,
+    before: TileData,
+    after: TileData,
+}
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+struct LabelChange {
+    axis: Axis,
+    index: usize,
+    before: Label,
+    after: Label,
+}
+
+/// Something done to a whole column or row. See `Board::edit_line`.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub(crate) enum LineStep {
+    Insert(Line),
+    /// Keeps what was removed, so it can be put back.
+    Remove(Line),
+    Move { axis: Axis, from: usize, to: usize },
+}
+
+impl LineStep {
+    fn apply(&self, board: &mut Board, direction: Direction) {
+        match (self, direction) {
+            (Self::Insert(line), Direction::Redo)
+            | (Self::Remove(line), Direction::Undo) => {
+                board.insert_line(line.clone());
+            },
+            (Self::Insert(line), Direction::Undo)
+            | (Self::Remove(line), Direction::Redo) => {
+                board.remove_line(line.axis, line.index);
+            },
+            (&Self::Move { axis, from, to }, Direction::Redo) => {
+                board.move_line(axis, from, to);
+            },
+            (&Self::Move { axis, from, to }, Direction::Undo) => {
+                board.move_line(axis, to, from);
+            },
+        }
+    }
+}
+
+/// Used to fold a run of typing into the same label or note into one step,
+/// instead of one step per character, and similarly for other runs of small
+/// changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Coalesce {
+    Label(Axis, usize),
+    Note(usize),
+    /// Everything painted in one drag.
+    Paint,
+}
+
+#[derive(Clone, Debug)]
+struct Step {
+    tiles: Vec<TileChange>,
+    labels: Vec<LabelChange>,
+    symmetry: Option<(Symmetry, Symmetry)>,
+    /// In the order they were done, before the rest of the changes.
+    lines: Vec<LineStep>,
+    /// The board from before and after a change that added or removed lines,
+    /// other than through `State::edit_line`. Everything else is empty when
+    /// this is set.
+    replaced: Option<Box<(Snapshot, Snapshot)>>,
+    coalesce: Option<Coalesce>,
+}
+
+impl Step {
+    fn is_empty(&self) -> bool {
+        self.tiles.is_empty()
+        && self.labels.is_empty()
+        && self.symmetry.is_none()
+        && self.lines.is_empty()
+        && self.replaced.is_none()
+    }
+
+    /// Folds `later`, which happened after `self`, into `self`. Neither step
+    /// can have changed any lines, since the indexes would no longer match.
+    fn absorb(&mut self, later: Step) {
+        debug_assert!(self.lines.is_empty() && later.lines.is_empty());
+        debug_assert!(self.replaced.is_none() && later.replaced.is_none());
+
+        for change in later.tiles {
+            match self.tiles.iter_mut().find(|c| c.index == change.index) {
+                Some(existing) => existing.after = change.after,
+                None => self.tiles.push(change),
+            }
+        }
+
+        for change in later.labels {
+            match self.labels.iter_mut()
+                .find(|c| c.axis == change.axis && c.index == change.index) {
+                Some(existing) => existing.after = change.after,
+                None => self.labels.push(change),
+            }
+        }
+
+        if let Some((later_before, after)) = later.symmetry {
+            let before = self.symmetry.map_or(later_before, |(before, _)| before);
+            self.symmetry = Some((before, after));
+        }
+
+        // Drop anything that was changed back, like a label that was typed
+        // into and then restored.
+        self.tiles.retain(|c| c.before != c.after);
+        self.labels.retain(|c| c.before != c.after);
+        if self.symmetry.is_some_and(|(before, after)| before == after) {
+            self.symmetry = None;
+        }
+    }
+
+    fn apply(&self, board: &mut Board, direction: Direction) {
+        macro_rules! pick {
+            ($change: expr) => {
+                match direction {
+                    Direction::Undo => &$change.before,
+                    Direction::Redo => &$change.after,
+                }
+            }
+        }
+
+        if let Some(replaced) = &self.replaced {
+            let (before, after) = &**replaced;
+            match direction {
+                Direction::Undo => before.restore(board),
+                Direction::Redo => after.restore(board),
+            }
+        }
+
+        // The other changes were found after the lines had changed, so the
+        // lines are redone first, and undone last.
+        if let Direction::Redo = direction {
+            for line in self.lines.iter() {
+                line.apply(board, direction);
+            }
+        }
+
+        for change in self.tiles.iter() {
+            board.tiles.tiles[change.index].clone_from(pick!(change));
+        }
+
+        for change in self.labels.iter() {
+            board.labels_mut(change.axis)[change.index].clone_from(pick!(change));
+        }
+
+        if let Some((before, after)) = self.symmetry {
+            // Set directly, rather than with `set_symmetry`, since the tiles
+            // it would have merged were already put back above.
+            board.symmetry = match direction {
+                Direction::Undo => before,
+                Direction::Redo => after,
+            };
+        }
+
+        if let Direction::Undo = direction {
+            for line in self.lines.iter().rev() {
+                line.apply(board, direction);
+            }
+        }
+    }
+}
+
+#[derive(Clone, Copy)]
+enum Direction {
+    Undo,
+    Redo,
+}
+
+/// The parts of a board that undo covers, from before a change.
+#[derive(Clone, Debug)]
+pub(crate) struct Snapshot {
+    tiles: Vec<TileData>,
+    column_labels: Vec<Label>,
+    row_labels: Vec<Label>,
+    symmetry: Symmetry,
+}
+
+impl Snapshot {
+    pub(crate) fn of(board: &Board) -> Self {
+        Self {
+            tiles: board.tiles.tiles.clone(),
+            column_labels: board.column_labels.clone(),
+            row_labels: board.row_labels.clone(),
+            symmetry: board.symmetry,
+        }
+    }
+
+    /// Puts the parts of `board` that were in the snapshot back.
+    fn restore(&self, board: &mut Board) {
+        board.tiles.tiles.clone_from(&self.tiles);
+        board.column_labels.clone_from(&self.column_labels);
+        board.row_labels.clone_from(&self.row_labels);
+        board.symmetry = self.symmetry;
+        board.resize_to_labels();
+    }
+
+    /// If `board` has a different number of lines than when the snapshot was
+    /// taken, the tiles can no longer be matched up, so the step holds the
+    /// whole board instead.
+    fn step_to(self, board: &Board) -> Step {
+        if self.column_labels.len() != board.column_labels.len()
+        || self.row_labels.len() != board.row_labels.len() {
+            return Step {
+                tiles: Vec::new(),
+                labels: Vec::new(),
+                symmetry: None,
+                lines: Vec::new(),
+                replaced: Some(Box::new((self, Snapshot::of(board)))),
+                coalesce: None,
+            };
+        }
+        debug_assert_eq!(self.tiles.len(), board.tiles.tiles.len());
+
+        let tiles = self.tiles.into_iter()
+            .zip(board.tiles.tiles.iter())
+            .enumerate()
+            .filter(|(_, (before, after))| before != *after)
+            .map(|(index, (before, after))| TileChange {
+                index,
+                before,
+                after: after.clone(),
+            })
+            .collect();
+
+        let mut labels = Vec::new();
+        for (axis, befores) in [
+            (Axis::Columns, self.column_labels),
+            (Axis::Rows, self.row_labels),
+        ] {
+            for (index, (before, after)) in befores.into_iter()
+                .zip(board.labels(axis).iter())
+                .enumerate() {
+                if before != *after {
+                    labels.push(LabelChange {
+                        axis,
+                        index,
+                        before,
+                        after: after.clone(),
+                    });
+                }
+            }
+        }
+
+        let symmetry = if self.symmetry == board.symmetry {
+            None
+        } else {
+            Some((self.symmetry, board.symmetry))
+        };
+
+        Step {
+            tiles,
+            labels,
+            symmetry,
+            lines: Vec::new(),
+            replaced: None,
+            coalesce: None,
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    /// Oldest first.
+    undo: std::collections::VecDeque<Step>,
+    /// Most recently undone last.
+    redo: Vec<Step>,
+}
+
+impl History {
+    /// Records the changes made to `board` since `before` was taken, as one
+    /// step. Does nothing if nothing changed.
+    pub(crate) fn record(
+        &mut self,
+        before: Snapshot,
+        board: &Board,
+        coalesce: Option<Coalesce>,
+    ) {
+        let mut step = before.step_to(board);
+        if step.is_empty() {
+            return;
+        }
+        // A whole board cannot be folded together with anything else.
+        let coalesce = coalesce.filter(|_| step.replaced.is_none());
+        step.coalesce = coalesce;
+
+        // Only fold into the last step if nothing was undone in between,
+        // since otherwise the last step is not what came right before.
+        if let (Some(last), true) = (self.undo.back_mut(), self.redo.is_empty()) {
+            if coalesce.is_some() && last.coalesce == coalesce {
+                last.absorb(step);
+                if last.is_empty() {
+                    self.undo.pop_back();
+                }
+                return;
+            }
+        }
+
+        self.push(step);
+    }
+
+    /// Records changes to whole lines, that were already made, as one step.
+    /// Does nothing if there are none.
+    pub(crate) fn record_lines(&mut self, lines: Vec<LineStep>) {
+        if lines.is_empty() {
+            return;
+        }
+
+        self.push(Step {
+            tiles: Vec::new(),
+            labels: Vec::new(),
+            symmetry: None,
+            lines,
+            replaced: None,
+            coalesce: None,
+        });
+    }
+
+    fn push(&mut self, step: Step) {
+        self.redo.clear();
+
+        if self.undo.len() >= UNDO_LIMIT {
+            self.undo.pop_front();
+        }
+        self.undo.push_back(step);
+    }
+
+    /// Ends any run of typing, so the next change is a separate step.
+    pub(crate) fn break_coalescing(&mut self) {
+        if let Some(last) = self.undo.back_mut() {
+            last.coalesce = None;
+        }
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// Returns whether there was anything to undo.
+    pub(crate) fn undo(&mut self, board: &mut Board) -> bool {
+        let Some(mut step) = self.undo.pop_back() else {
+            return false;
+        };
+
+        step.apply(board, Direction::Undo);
+        step.coalesce = None;
+        self.redo.push(step);
+
+        true
+    }
+
+    /// Returns whether there was anything to redo.
+    pub(crate) fn redo(&mut self, board: &mut Board) -> bool {
+        let Some(step) = self.redo.pop() else {
+            return false;
+        };
+
+        step.apply(board, Direction::Redo);
+        self.undo.push_back(step);
+
+        true
+    }
+}
+
+#[cfg(test)]
+fn test_state() -> crate::State {
+    let labels = ["a", "b", "c"].map(str::to_owned).to_vec();
+
+    crate::State::from(Board::with_labels(labels).unwrap())
+}
+
+#[test]
+fn undo_and_redo_put_back_statuses_and_the_symmetry_together() {
+    use crate::Status;
+
+    let mut state = test_state();
+    state.edit_board(|board| board.set_status(1, 0, Status::Fails)).unwrap();
+    state.edit_board(|board| {
+        board.set_symmetry(Symmetry::Symmetric(<_>::default()))
+    }).unwrap();
+    assert_eq!(state.board().status(0, 1), Ok(Status::Fails));
+
+    assert!(state.undo());
+    assert_eq!(state.board().symmetry(), Symmetry::Asymmetric);
+    assert_eq!(state.board().status(0, 1), Ok(Status::Unchecked));
+    assert_eq!(state.board().status(1, 0), Ok(Status::Fails));
+
+    assert!(state.undo());
+    assert_eq!(state.board().status(1, 0), Ok(Status::Unchecked));
+    assert!(!state.undo());
+
+    assert!(state.redo());
+    assert!(state.redo());
+    assert_eq!(state.board().status(0, 1), Ok(Status::Fails));
+    assert!(!state.redo());
+}
+
+#[test]
+fn typing_into_one_label_is_one_step() {
+    let mut state = test_state();
+
+    for text in ["a1", "a12", "a1"] {
+        state.edit_board_coalescing(
+            Some(Coalesce::Label(Axis::Rows, 0)),
+            |board| board.labels_mut(Axis::Rows)[0] = text.to_owned(),
+        );
+    }
+    state.history.break_coalescing();
+    state.edit_board_coalescing(
+        Some(Coalesce::Label(Axis::Rows, 0)),
+        |board| board.labels_mut(Axis::Rows)[0] = "z".to_owned(),
+    );
+
+    assert!(state.undo());
+    assert_eq!(state.board().labels(Axis::Rows)[0], "a1");
+    assert!(state.undo());
+    assert_eq!(state.board().labels(Axis::Rows)[0], "a");
+    assert!(!state.history().can_undo());
+}
+
+#[test]
+fn typing_into_a_label_and_changing_it_back_leaves_nothing_to_undo() {
+    let mut state = test_state();
+
+    for text in ["ab", "a"] {
+        state.edit_board_coalescing(
+            Some(Coalesce::Label(Axis::Columns, 0)),
+            |board| board.labels_mut(Axis::Columns)[0] = text.to_owned(),
+        );
+    }
+
+    assert!(!state.history().can_undo());
+}
+
+#[test]
+fn only_the_most_recent_steps_are_kept() {
+    use crate::Status;
+
+    let mut state = test_state();
+
+    for _ in 0..UNDO_LIMIT + 1 {
+        state.edit_board(|board| {
+            let next = board.status(0, 0).unwrap().next();
+            board.set_status(0, 0, next)
+        }).unwrap();
+    }
+
+    let mut undone = 0;
+    while state.undo() {
+        undone += 1;
+    }
+
+    assert_eq!(undone, UNDO_LIMIT);
+    // The first change was forgotten, so it stays done.
+    assert_eq!(state.board().status(0, 0), Ok(Status::Unchecked.next()));
+}
+
+#[test]
+fn a_new_change_after_undoing_forgets_what_could_be_redone() {
+    let mut state = test_state();
+
+    state.edit_board(|board| board.labels_mut(Axis::Columns)[0] = "x".to_owned());
+    state.undo();
+    state.edit_board(|board| board.labels_mut(Axis::Columns)[1] = "y".to_owned());
+
+    assert!(!state.history().can_redo());
+    assert_eq!(state.board().labels(Axis::Columns)[0], "a");
+}
+
+#[test]
+fn undoing_line_edits_puts_the_lines_and_their_tiles_back() {
+    use crate::{LineOp, Status};
+
+    let labels = ["a", "b", "c", "d"].map(str::to_owned).to_vec();
+    let mut state = crate::State::from(Board::with_labels(labels).unwrap());
+    state.edit_board(|board| board.set_status(1, 2, Status::Fails)).unwrap();
+    state.edit_line(Axis::Rows, LineOp::Remove { index: 2 }).unwrap();
+    state.edit_line(Axis::Columns, LineOp::Move { from: 1, to: 0 }).unwrap();
+    state.edit_line(Axis::Columns, LineOp::Insert {
+        index: 4,
+        label: "e".to_owned(),
+    }).unwrap();
+    // A change after the lines changed is recorded against the new indexes.
+    state.edit_board(|board| board.set_status(4, 1, Status::Passes)).unwrap();
+
+    assert!(state.undo());
+    assert!(state.undo());
+    assert_eq!(state.board().labels(Axis::Columns), ["b", "a", "c", "d"]);
+    assert!(state.undo());
+    assert!(state.undo());
+    assert_eq!(state.board().labels(Axis::Rows), ["a", "b", "c", "d"]);
+    assert_eq!(state.board().status(1, 2), Ok(Status::Fails));
+
+    while state.redo() {}
+    assert_eq!(state.board().labels(Axis::Columns), ["b", "a", "c", "d", "e"]);
+    assert_eq!(state.board().labels(Axis::Rows), ["a", "b", "d"]);
+    assert_eq!(state.board().status(4, 1), Ok(Status::Passes));
+    assert_eq!(state.board().progress().counts.get(Status::Fails), 0);
+}
+
+#[test]
+fn undoing_an_edit_that_added_lines_puts_the_whole_board_back() {
+    use crate::Status;
+
+    let mut state = test_state();
+    state.edit_board(|board| board.set_status(0, 1, Status::Fails)).unwrap();
+    state.edit_board(|board| {
+        let labels = ["c", "d", "e", "a"].map(str::to_owned);
+        board.sync_labels(Axis::Columns, &labels)
+    }).unwrap();
+    assert_eq!(state.board().status(3, 1), Ok(Status::Fails));
+
+    assert!(state.undo());
+    assert_eq!(state.board().labels(Axis::Columns), ["a", "b", "c"]);
+    assert_eq!(state.board().status(0, 1), Ok(Status::Fails));
+    assert!(state.undo());
+    assert_eq!(state.board().progress().counts.get(Status::Fails), 0);
+
+    while state.redo() {}
+    assert_eq!(state.board().labels(Axis::Columns), ["c", "d", "e", "a"]);
+    assert_eq!(state.board().status(3, 1), Ok(Status::Fails));
+}
@@ -0,0 +1,245 @@
+//! Turning the input flags from the platform layer into what the eye does.
+
+use crate::{
+    Dir,
+    InputFlags,
+    INPUT_UP_PRESSED,
+    INPUT_DOWN_PRESSED,
+    INPUT_LEFT_PRESSED,
+    INPUT_RIGHT_PRESSED,
+    INPUT_UP_DOWN,
+    INPUT_DOWN_DOWN,
+    INPUT_LEFT_DOWN,
+    INPUT_RIGHT_DOWN,
+    INPUT_INTERACT_PRESSED,
+};
+
+const ANY_DIRECTION_PRESSED: InputFlags = INPUT_UP_PRESSED
+    | INPUT_DOWN_PRESSED
+    | INPUT_LEFT_PRESSED
+    | INPUT_RIGHT_PRESSED;
+
+const ANY_DIRECTION_DOWN: InputFlags = INPUT_UP_DOWN
+    | INPUT_DOWN_DOWN
+    | INPUT_LEFT_DOWN
+    | INPUT_RIGHT_DOWN;
+
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Input {
+    NoChange,
+    Dir(Dir),
+    Interact,
+}
+
+impl Input {
+    /// Looks at which directions are held down, and ignores the `_PRESSED`
+    /// direction flags. Opposite directions cancel each other out.
+    pub(crate) fn from_flags(flags: InputFlags) -> Self {
+        use Input::*;
+        use crate::Dir::*;
+
+        if INPUT_INTERACT_PRESSED & flags != 0 {
+            return Interact;
+        }
+
+        let is_down = |flag: InputFlags| flags & flag != 0;
+
+        let up = is_down(INPUT_UP_DOWN) && !is_down(INPUT_DOWN_DOWN);
+        let down = is_down(INPUT_DOWN_DOWN) && !is_down(INPUT_UP_DOWN);
+        let left = is_down(INPUT_LEFT_DOWN) && !is_down(INPUT_RIGHT_DOWN);
+        let right = is_down(INPUT_RIGHT_DOWN) && !is_down(INPUT_LEFT_DOWN);
+
+        match (up, down, left, right) {
+            (true, _, true, _) => Dir(UpLeft),
+            (true, _, _, true) => Dir(UpRight),
+            (_, true, true, _) => Dir(DownLeft),
+            (_, true, _, true) => Dir(DownRight),
+            (true, _, _, _) => Dir(Up),
+            (_, true, _, _) => Dir(Down),
+            (_, _, true, _) => Dir(Left),
+            (_, _, _, true) => Dir(Right),
+            (false, false, false, false) => NoChange,
+        }
+    }
+}
+
+/// How a held direction repeats, counted in frames.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct KeyRepeat {
+    /// How long a direction has to be held after the first move before it
+    /// starts repeating.
+    pub delay: u16,
+    /// How long to wait between each move once it is repeating. 0 acts the
+    /// same as 1, that is, a move every frame.
+    pub interval: u16,
+}
+
+impl Default for KeyRepeat {
+    fn default() -> Self {
+        Self {
+            delay: 18,
+            interval: 4,
+        }
+    }
+}
+
+/// How the eye moves when a direction key is held down.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum EyeMovement {
+    /// One tile when a key is pressed, then more as described by the
+    /// `KeyRepeat`, for as long as the key is held.
+    Repeating(KeyRepeat),
+    /// One tile each time a key is pressed.
+    PerPress,
+}
+
+impl Default for EyeMovement {
+    fn default() -> Self {
+        EyeMovement::Repeating(KeyRepeat::default())
+    }
+}
+
+/// Keeps track of how long the direction keys have been held.
+#[derive(Debug, Default)]
+pub(crate) struct HeldDirections {
+    held_frames: u16,
+}
+
+impl HeldDirections {
+    /// Should be called once per frame.
+    pub(crate) fn input(
+        &mut self,
+        flags: InputFlags,
+        eye_movement: EyeMovement,
+    ) -> Input {
+        // Pressing a key moves right away, in the direction of every key held
+        // at the time, so holding one key and then pressing another still
+        // moves diagonally.
+        let moves = if flags & ANY_DIRECTION_PRESSED != 0 {
+            self.held_frames = 0;
+            true
+        } else if flags & ANY_DIRECTION_DOWN != 0 {
+            self.held_frames = self.held_frames.saturating_add(1);
+
+            match eye_movement {
+                EyeMovement::PerPress => false,
+                EyeMovement::Repeating(KeyRepeat { delay, interval }) => {
+                    self.held_frames >= delay
+                    && (self.held_frames - delay).is_multiple_of(interval.max(1))
+                },
+            }
+        } else {
+            self.held_frames = 0;
+            false
+        };
+
+        Input::from_flags(if moves {
+            flags
+        } else {
+            flags & !ANY_DIRECTION_DOWN
+        })
+    }
+}
+
+#[test]
+fn from_flags_gives_the_right_direction_for_every_combination() {
+    use Input::*;
+    use crate::Dir::*;
+
+    let flags = [INPUT_UP_DOWN, INPUT_DOWN_DOWN, INPUT_LEFT_DOWN, INPUT_RIGHT_DOWN];
+
+    for combination in 0..1 << flags.len() {
+        let mut input_flags = 0;
+        let mut held = [false; 4];
+        for (i, &flag) in flags.iter().enumerate() {
+            if combination & (1 << i) != 0 {
+                input_flags |= flag;
+                held[i] = true;
+            }
+        }
+        let [up, down, left, right] = held;
+
+        let vertical = match (up, down) {
+            (true, false) => Some(Up),
+            (false, true) => Some(Down),
+            _ => None,
+        };
+        let horizontal = match (left, right) {
+            (true, false) => Some(Left),
+            (false, true) => Some(Right),
+            _ => None,
+        };
+
+        let expected = match (vertical, horizontal) {
+            (None, None) => NoChange,
+            (Some(dir), None) | (None, Some(dir)) => Dir(dir),
+            (Some(Up), Some(Left)) => Dir(UpLeft),
+            (Some(Up), Some(Right)) => Dir(UpRight),
+            (Some(Down), Some(Left)) => Dir(DownLeft),
+            (Some(Down), Some(Right)) => Dir(DownRight),
+            (Some(_), Some(_)) => unreachable!(),
+        };
+
+        assert_eq!(Input::from_flags(input_flags), expected, "{input_flags:#b}");
+
+        // The pressed flags on their own do not move anything.
+        assert_eq!(
+            Input::from_flags(input_flags | ANY_DIRECTION_PRESSED),
+            expected,
+            "{input_flags:#b}",
+        );
+
+        assert_eq!(
+            Input::from_flags(input_flags | INPUT_INTERACT_PRESSED),
+            Interact,
+            "{input_flags:#b}",
+        );
+    }
+}
+
+#[cfg(test)]
+fn frames_that_move(eye_movement: EyeMovement, frame_count: u16) -> Vec<u16> {
+    let mut held = HeldDirections::default();
+
+    (0..frame_count)
+        .filter(|&frame| {
+            let mut flags = INPUT_RIGHT_DOWN;
+            if frame == 0 {
+                flags |= INPUT_RIGHT_PRESSED;
+            }
+
+            held.input(flags, eye_movement) == Input::Dir(Dir::Right)
+        })
+        .collect()
+}
+
+#[test]
+fn held_directions_move_once_then_repeat_after_the_delay() {
+    let eye_movement = EyeMovement::Repeating(KeyRepeat {
+        delay: 10,
+        interval: 3,
+    });
+
+    assert_eq!(frames_that_move(eye_movement, 20), [0, 10, 13, 16, 19]);
+}
+
+#[test]
+fn per_press_movement_does_not_repeat() {
+    assert_eq!(frames_that_move(EyeMovement::PerPress, 100), [0]);
+}
+
+#[test]
+fn letting_go_restarts_the_delay() {
+    let mut held = HeldDirections::default();
+    let eye_movement = EyeMovement::Repeating(KeyRepeat {
+        delay: 2,
+        interval: 1,
+    });
+
+    held.input(INPUT_UP_PRESSED | INPUT_UP_DOWN, eye_movement);
+    held.input(INPUT_UP_DOWN, eye_movement);
+    held.input(0, eye_movement);
+
+    assert_eq!(held.input(INPUT_UP_DOWN, eye_movement), Input::NoChange);
+    assert_eq!(held.input(INPUT_UP_DOWN, eye_movement), Input::Dir(Dir::Up));
+}
@@ -0,0 +1,324 @@
+//! Summaries of a board, for pasting into pull requests and wikis.
+//!
+//! Both formats have the overall progress, then a table with a cell for the
+//! status of each pair, and the progress of each row at the end of the row,
+//! then any notes. On symmetric boards, only the tiles that count towards the
+//! progress are filled in, so each pair is only shown once.
+
+use crate::{label_or_index, Axis, Board, Status};
+
+/// A short mark for the status, that still reads well in a narrow table.
+fn status_mark(status: Status) -> &'static str {
+    match status {
+        Status::Unchecked => " ",
+        Status::Passes => "✔",
+        Status::Fails => "✘",
+        Status::Blocked => "■",
+        Status::NeedsRecheck => "?",
+        Status::NotApplicable => "–",
+    }
+}
+
+/// The text for the progress of the whole board, like "3/6 checked (50%)".
+fn progress_summary(board: &Board) -> String {
+    let progress = board.progress();
+
+    format!(
+        "{}/{} checked ({}%)",
+        progress.checked,
+        progress.total,
+        progress.percent(),
+    )
+}
+
+/// The text for the progress of a row, like "1/3".
+fn row_summary(board: &Board, row: usize) -> String {
+    board.line_progress(Axis::Rows, row)
+        .map(|progress| format!("{}/{}", progress.checked, progress.total))
+        .unwrap_or_default()
+}
+
+/// Each note, along with the labels of its pair.
+fn notes(board: &Board) -> impl Iterator<Item = (String, String, &str)> {
+    board.notes().map(|(column, row, note)| (
+        label_or_index(board.labels(Axis::Columns), column),
+        label_or_index(board.labels(Axis::Rows), row),
+        note,
+    ))
+}
+
+fn escape_markdown(text: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '\\' | '|' | '*' | '_' | '`' | '[' | ']' | '<' | '>' | '#' => {
+                output.push('\\');
+                output.push(c);
+            },
+            // Line breaks would end the table row, or the list item.
+            '\n' => output.push_str("<br>"),
+            '\r' => {},
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// A Markdown table of the board, with GitHub's table syntax. `title` is used
+/// as the heading.
+pub fn markdown(board: &Board, title: &str) -> String {
+    use core::fmt::Write;
+
+    let mut output = String::with_capacity(1024);
+
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(output, "# {}\n", escape_markdown(title));
+    let _ = writeln!(output, "**{}**\n", progress_summary(board));
+
+    // This doubles as a key for the marks in the table.
+    let counts: Vec<_> = board.progress().counts.iter()
+        .filter(|&(_, count)| count > 0)
+        .map(|(status, count)| format!(
+            "{} {}: {count}",
+            status_mark(status),
+            status.display_name(),
+        ).trim_start().to_owned())
+        .collect();
+    let _ = writeln!(output, "{}\n", counts.join(" · "));
+
+    let column_labels = board.labels(Axis::Columns);
+
+    output.push_str("|   |");
+    for column in 0..column_labels.len() {
+        let _ = write!(
+            output,
+            " {} |",
+            escape_markdown(&label_or_index(column_labels, column)),
+        );
+    }
+    output.push_str(" Checked |\n|---|");
+    for _ in column_labels {
+        output.push_str(":-:|");
+    }
+    output.push_str("--:|\n");
+
+    for row in 0..board.labels(Axis::Rows).len() {
+        let _ = write!(
+            output,
+            "| {} |",
+            escape_markdown(&label_or_index(board.labels(Axis::Rows), row)),
+        );
+
+        for column in 0..column_labels.len() {
+            let mark = match board.status(column, row) {
+                Ok(status) if board.is_counted(column, row) => status_mark(status),
+                _ => " ",
+            };
+            let _ = write!(output, " {mark} |");
+        }
+
+        let _ = writeln!(output, " {} |", row_summary(board, row));
+    }
+
+    let mut notes = notes(board).peekable();
+    if notes.peek().is_some() {
+        output.push_str("\n## Notes\n\n");
+
+        for (column, row, note) in notes {
+            let _ = writeln!(
+                output,
+                "* **{} × {}**: {}",
+                escape_markdown(&column),
+                escape_markdown(&row),
+                escape_markdown(note),
+            );
+        }
+    }
+
+    output
+}
+
+/// `line_break` replaces each line break, since they are ignored in HTML.
+fn escape_html(text: &str, line_break: &str) -> String {
+    let mut output = String::with_capacity(text.len());
+
+    for c in text.chars() {
+        match c {
+            '&' => output.push_str("&amp;"),
+            '<' => output.push_str("&lt;"),
+            '>' => output.push_str("&gt;"),
+            '"' => output.push_str("&quot;"),
+            '\n' => output.push_str(line_break),
+            '\r' => {},
+            _ => output.push(c),
+        }
+    }
+
+    output
+}
+
+/// The background colour of the cells with each status, roughly matching the
+/// tile sprites.
+fn status_colour(status: Status) -> &'static str {
+    match status {
+        Status::Unchecked => "#ffffff",
+        Status::Passes => "#8ee08e",
+        Status::Fails => "#f28b82",
+        Status::Blocked => "#ffb937",
+        Status::NeedsRecheck => "#fff176",
+        Status::NotApplicable => "#c8c8c8",
+    }
+}
+
+const HTML_STYLE: &str = "\
+body { font-family: sans-serif; margin: 2em; }
+table { border-collapse: collapse; }
+th, td { border: 1px solid #999; padding: 0.25em 0.5em; }
+td { text-align: center; }
+td.total { text-align: right; }
+td.mirror { background: #eeeeee; }
+";
+
+/// A standalone HTML page for the board, with the cells coloured by status.
+/// `title` is used as the page title and heading.
+pub fn html(board: &Board, title: &str) -> String {
+    use core::fmt::Write;
+
+    let mut output = String::with_capacity(4096);
+
+    let title = escape_html(title, " ");
+
+    // Writing to a `String` cannot fail.
+    let _ = writeln!(output, "<!DOCTYPE html>");
+    let _ = writeln!(output, "<html>\n<head>\n<meta charset=\"utf-8\">");
+    let _ = writeln!(output, "<title>{title}</title>");
+    let _ = write!(output, "<style>\n{HTML_STYLE}");
+    for status in Status::ALL {
+        let _ = writeln!(
+            output,
+            ".{} {{ background: {}; }}",
+            status.name(),
+            status_colour(status),
+        );
+    }
+    let _ = writeln!(output, "</style>\n</head>\n<body>");
+    let _ = writeln!(output, "<h1>{title}</h1>");
+    let _ = writeln!(output, "<p><strong>{}</strong></p>", progress_summary(board));
+
+    let _ = writeln!(output, "<ul>");
+    for (status, count) in board.progress().counts.iter() {
+        let _ = writeln!(
+            output,
+            "<li><span class=\"{}\">{}</span> {}: {count}</li>",
+            status.name(),
+            status_mark(status),
+            status.display_name(),
+        );
+    }
+    let _ = writeln!(output, "</ul>");
+
+    let column_labels = board.labels(Axis::Columns);
+
+    let _ = write!(output, "<table>\n<tr><th></th>");
+    for column in 0..column_labels.len() {
+        let _ = write!(
+            output,
+            "<th>{}</th>",
+            escape_html(&label_or_index(column_labels, column), "<br>"),
+        );
+    }
+    let _ = writeln!(output, "<th>Checked</th></tr>");
+
+    for row in 0..board.labels(Axis::Rows).len() {
+        let _ = write!(
+            output,
+            "<tr><th>{}</th>",
+            escape_html(&label_or_index(board.labels(Axis::Rows), row), "<br>"),
+        );
+
+        for column in 0..column_labels.len() {
+            match board.status(column, row) {
+                Ok(status) if board.is_counted(column, row) => {
+                    let note = board.note(column, row).unwrap_or_default();
+                    let _ = write!(
+                        output,
+                        "<td class=\"{}\" title=\"{}{}{}\">{}</td>",
+                        status.name(),
+                        status.display_name(),
+                        if note.is_empty() { "" } else { ": " },
+                        escape_html(note, "&#10;"),
+                        status_mark(status),
+                    );
+                },
+                _ => {
+                    let _ = write!(output, "<td class=\"mirror\"></td>");
+                },
+            }
+        }
+
+        let _ = writeln!(
+            output,
+            "<td class=\"total\">{}</td></tr>",
+            row_summary(board, row),
+        );
+    }
+    let _ = writeln!(output, "</table>");
+
+    let mut notes = notes(board).peekable();
+    if notes.peek().is_some() {
+        let _ = writeln!(output, "<h2>Notes</h2>\n<dl>");
+
+        for (column, row, note) in notes {
+            let _ = writeln!(
+                output,
+                "<dt>{} × {}</dt><dd>{}</dd>",
+                escape_html(&column, "<br>"),
+                escape_html(&row, "<br>"),
+                escape_html(note, "<br>"),
+            );
+        }
+
+        let _ = writeln!(output, "</dl>");
+    }
+
+    let _ = writeln!(output, "</body>\n</html>");
+
+    output
+}
+
+#[cfg(test)]
+fn example_board() -> Board {
+    let mut board = Board::with_axis_labels(
+        ["a|b", "c", "d"].map(str::to_owned).to_vec(),
+        ["<x>", "", "z"].map(str::to_owned).to_vec(),
+    ).unwrap();
+    board.set_status(0, 0, Status::Passes).unwrap();
+    board.set_status(1, 1, Status::Fails).unwrap();
+    board.set_note(1, 1, "breaks when\n<b> is held".to_owned()).unwrap();
+
+    board
+}
+
+#[test]
+fn markdown_reports_have_a_row_per_label_and_the_notes() {
+    let report = markdown(&example_board(), "Example");
+
+    assert!(report.contains("**2/9 checked (22%)**"));
+    assert!(report.contains("|   | a\\|b | c | d | Checked |"));
+    assert!(report.contains("| \\<x\\> | ✔ |   |   | 1/3 |"));
+    // Empty labels are shown as their number.
+    assert!(report.contains("| 1 |   | ✘ |   | 1/3 |"));
+    assert!(report.contains("* **c × 1**: breaks when<br>\\<b\\> is held"));
+}
+
+#[test]
+fn html_reports_escape_and_colour_each_cell() {
+    let report = html(&example_board(), "Example");
+
+    assert!(report.contains("<th>&lt;x&gt;</th><td class=\"passes\""));
+    assert!(report.contains("title=\"Fails: breaks when&#10;&lt;b&gt; is held\">✘</td>"));
+    assert!(report.contains("<td class=\"total\">1/3</td>"));
+    assert!(!report.contains("<b>"));
+}
@@ -0,0 +1,95 @@
+//! Labels from the variants of an enum in a Rust source file, for checking
+//! every variant of one enum against every variant of another, or itself.
+//! See `Board::sync_labels` for keeping a board up to date as the enum
+//! changes.
+
+use crate::Label;
+
+/// One-based, to match what text editors show.
+pub type LineNumber = usize;
+
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Error {
+    /// The source is not valid Rust.
+    Malformed { line: LineNumber, reason: String },
+    /// There is no enum with the given name in the file.
+    NotFound(String),
+}
+
+impl core::fmt::Display for Error {
+    fn fmt(&self, f: &mut core::fmt::Formatter<'_>) -> core::fmt::Result {
+        match self {
+            Self::Malformed { line, reason } => write!(f, "line {line}: {reason}"),
+            Self::NotFound(name) => write!(f, "there is no enum named \"{name}\""),
+        }
+    }
+}
+
+impl std::error::Error for Error {}
+
+/// Looks through `items`, and the modules written inline in them, for the
+/// first enum named `name`.
+fn find_enum<'items>(items: &'items [syn::Item], name: &str) -> Option<&'items syn::ItemEnum> {
+    items.iter().find_map(|item| match item {
+        syn::Item::Enum(item_enum) if item_enum.ident == name => Some(item_enum),
+        syn::Item::Mod(syn::ItemMod { content: Some((_, items)), .. }) => {
+            find_enum(items, name)
+        },
+        _ => None,
+    })
+}
+
+/// Returns the names of the variants of the first enum named `name` in
+/// `source`, in the order they are declared. Enums in modules written inline
+/// in the file are found too, but not ones declared inside functions.
+/// Variants behind a `#[cfg(...)]` are included either way.
+pub fn variant_labels(source: &str, name: &str) -> Result<Vec<Label>, Error> {
+    let file = syn::parse_file(source).map_err(|e| Error::Malformed {
+        line: e.span().start().line,
+        reason: e.to_string(),
+    })?;
+
+    let item_enum = find_enum(&file.items, name)
+        .ok_or_else(|| Error::NotFound(name.to_owned()))?;
+
+    Ok(
+        item_enum.variants.iter()
+            .map(|variant| variant.ident.to_string())
+            .collect()
+    )
+}
+
+#[test]
+fn variants_are_found_in_nested_modules_with_any_shape() {
+    let source = r#"
+        enum Other { A }
+
+        mod draw {
+            /// Docs.
+            pub enum SpriteKind<'a, T: Clone> where T: Default {
+                Blank,
+                #[cfg(test)]
+                Arrow(Dir, &'a T),
+                Label { text: [u8; 4] },
+                Dir = 3,
+            }
+        }
+    "#;
+
+    assert_eq!(
+        variant_labels(source, "SpriteKind"),
+        Ok(["Blank", "Arrow", "Label", "Dir"].map(str::to_owned).to_vec())
+    );
+}
+
+#[test]
+fn missing_enums_and_bad_source_are_reported() {
+    assert_eq!(
+        variant_labels("struct SpriteKind;", "SpriteKind"),
+        Err(Error::NotFound("SpriteKind".to_owned()))
+    );
+    assert!(matches!(
+        variant_labels("\n\nenum SpriteKind {", "SpriteKind"),
+        Err(Error::Malformed { line: 3, .. })
+    ));
+}
@@ -0,0 +1,445 @@
+//! Editing a piece of text with a caret and a selection, a grapheme cluster at
+//! a time, so accented letters and emoji made of several `char`s are treated
+//! as one.
+
+use unicode_segmentation::UnicodeSegmentation;
+
+pub type TextKeys = u16;
+
+// These should be set on the frame the key was pressed, and on the frames where
+// it repeats from being held down.
+pub const TEXT_LEFT: TextKeys           = 0b0000_0000_0000_0001;
+pub const TEXT_RIGHT: TextKeys          = 0b0000_0000_0000_0010;
+pub const TEXT_HOME: TextKeys           = 0b0000_0000_0000_0100;
+pub const TEXT_END: TextKeys            = 0b0000_0000_0000_1000;
+
+/// Makes moving the caret, or clicking, extend the selection instead.
+pub const TEXT_SHIFT_DOWN: TextKeys     = 0b0000_0000_0001_0000;
+/// Makes left, right, backspace and delete go a word at a time.
+pub const TEXT_CTRL_DOWN: TextKeys      = 0b0000_0000_0010_0000;
+
+pub const TEXT_SELECT_ALL: TextKeys     = 0b0000_0000_0100_0000;
+/// The selected text ends up in `Edit::copied`.
+pub const TEXT_COPY: TextKeys           = 0b0000_0000_1000_0000;
+/// Like `TEXT_COPY`, but also removes the selected text.
+pub const TEXT_CUT: TextKeys            = 0b0000_0001_0000_0000;
+
+// These are not used by `Cursor::edit`, but by whatever decides which text is
+// being edited, except that `TEXT_ENTER` starts a new line in multiline text.
+/// Moves to the next text box, or the previous one with `TEXT_SHIFT_DOWN`.
+pub const TEXT_TAB: TextKeys            = 0b0000_0010_0000_0000;
+/// Keeps the edit.
+pub const TEXT_ENTER: TextKeys          = 0b0000_0100_0000_0000;
+/// Throws the edit away.
+pub const TEXT_ESCAPE: TextKeys         = 0b0000_1000_0000_0000;
+
+/// What was typed this frame.
+#[derive(Clone, Debug, Default)]
+pub struct TextInput {
+    /// One `char` per key, in the order they were typed. A `'\0'` ends the
+    /// input early. Backspace is `'\u{8}'` and delete is `'\u{7f}'`.
+    pub chars: [char; 8],
+    pub keys: TextKeys,
+    /// What was on the clipboard, if it was pasted this frame.
+    pub pasted: Option<String>,
+}
+
+/// Where the caret is, and what is selected. The indexes are in bytes, and are
+/// always on the boundary of a grapheme cluster.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub(crate) struct Cursor {
+    pub(crate) caret: usize,
+    /// The other end of the selection. The same as `caret` if nothing is
+    /// selected.
+    pub(crate) anchor: usize,
+}
+
+/// What `Cursor::edit` did.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub(crate) struct Edit {
+    /// Whether the text was changed.
+    pub(crate) changed: bool,
+    /// Whether the text was changed, or the caret moved.
+    pub(crate) moved: bool,
+    /// What should be put on the clipboard, if anything.
+    pub(crate) copied: Option<String>,
+}
+
+fn previous_grapheme(text: &str, i: usize) -> usize {
+    text[..i].grapheme_indices(true)
+        .next_back()
+        .map_or(0, |(start, _)| start)
+}
+
+fn next_grapheme(text: &str, i: usize) -> usize {
+    text[i..].graphemes(true)
+        .next()
+        .map_or(text.len(), |grapheme| i + grapheme.len())
+}
+
+fn is_whitespace(s: &str) -> bool {
+    s.chars().all(char::is_whitespace)
+}
+
+/// The start of the word before `i`, skipping any whitespace in between.
+fn previous_word(text: &str, i: usize) -> usize {
+    text[..i].split_word_bound_indices()
+        .rev()
+        .find(|(_, word)| !is_whitespace(word))
+        .map_or(0, |(start, _)| start)
+}
+
+/// The end of the word after `i`, skipping any whitespace in between.
+fn next_word(text: &str, i: usize) -> usize {
+    text[i..].split_word_bound_indices()
+        .find(|(_, word)| !is_whitespace(word))
+        .map_or(text.len(), |(start, word)| i + start + word.len())
+}
+
+/// Where the caret would go from `i`, one grapheme or one word back or
+/// forward.
+fn step(text: &str, i: usize, backwards: bool, by_word: bool) -> usize {
+    match (backwards, by_word) {
+        (true, false) => previous_grapheme(text, i),
+        (true, true) => previous_word(text, i),
+        (false, false) => next_grapheme(text, i),
+        (false, true) => next_word(text, i),
+    }
+}
+
+impl Cursor {
+    pub(crate) fn at(i: usize) -> Self {
+        Self {
+            caret: i,
+            anchor: i,
+        }
+    }
+
+    pub(crate) fn selection(&self) -> core::ops::Range<usize> {
+        self.caret.min(self.anchor)..self.caret.max(self.anchor)
+    }
+
+    /// Keeps the cursor inside `text`, for when the text was changed by
+    /// something else, like an undo.
+    pub(crate) fn clamp(&mut self, text: &str) {
+        for i in [&mut self.caret, &mut self.anchor] {
+            if *i > text.len() || !text.is_char_boundary(*i) {
+                *i = text.len();
+            }
+        }
+    }
+
+    /// Moves the caret to `i`, and either keeps the other end of the
+    /// selection where it is, or leaves nothing selected.
+    pub(crate) fn move_to(&mut self, i: usize, extend_selection: bool) {
+        self.caret = i;
+        if !extend_selection {
+            self.anchor = i;
+        }
+    }
+
+    /// Replaces the selection with `s`.
+    fn replace_selection(&mut self, text: &mut String, s: &str) {
+        let selection = self.selection();
+        text.replace_range(selection.clone(), s);
+        *self = Self::at(selection.start + s.len());
+    }
+
+    /// Applies what was typed this frame to `text`. If `multiline` is set,
+    /// enter and pasted newlines start a new line, instead of being dropped,
+    /// or turned into spaces, respectively.
+    pub(crate) fn edit(
+        &mut self,
+        text: &mut String,
+        input: &TextInput,
+        multiline: bool,
+    ) -> Edit {
+        self.clamp(text);
+
+        let before = *self;
+        let mut edit = Edit::default();
+
+        let keys = input.keys;
+        let shift = keys & TEXT_SHIFT_DOWN != 0;
+        let by_word = keys & TEXT_CTRL_DOWN != 0;
+
+        if keys & TEXT_SELECT_ALL != 0 {
+            self.anchor = 0;
+            self.caret = text.len();
+        }
+
+        if keys & (TEXT_COPY | TEXT_CUT) != 0 && !self.selection().is_empty() {
+            edit.copied = Some(text[self.selection()].to_owned());
+
+            if keys & TEXT_CUT != 0 {
+                self.replace_selection(text, "");
+                edit.changed = true;
+            }
+        }
+
+        for (key, backwards) in [(TEXT_LEFT, true), (TEXT_RIGHT, false)] {
+            if keys & key == 0 {
+                continue;
+            }
+
+            let selection = self.selection();
+            self.caret = if !shift && !selection.is_empty() {
+                // Moving with something selected goes to that end of it.
+                if backwards { selection.start } else { selection.end }
+            } else {
+                step(text, self.caret, backwards, by_word)
+            };
+
+            if !shift {
+                self.anchor = self.caret;
+            }
+        }
+
+        if keys & TEXT_HOME != 0 {
+            self.move_to(0, shift);
+        }
+
+        if keys & TEXT_END != 0 {
+            self.move_to(text.len(), shift);
+        }
+
+        if multiline && keys & TEXT_ENTER != 0 {
+            self.replace_selection(text, "\n");
+            edit.changed = true;
+        }
+
+        let mut buffer = [0; 4];
+        for c in input.chars {
+            match c {
+                '\0' => break,
+                '\u{8}' | '\u{7f}' => {
+                    if self.selection().is_empty() {
+                        // Select what should be deleted, then delete it below.
+                        self.anchor = step(text, self.caret, c == '\u{8}', by_word);
+                    }
+
+                    if !self.selection().is_empty() {
+                        self.replace_selection(text, "");
+                        edit.changed = true;
+                    }
+                }
+                // Other control characters, like tabs, would not show up
+                // properly.
+                _ if c.is_control() && !(multiline && c == '\n') => {}
+                _ => {
+                    self.replace_selection(text, c.encode_utf8(&mut buffer));
+                    edit.changed = true;
+                }
+            }
+        }
+
+        if let Some(pasted) = &input.pasted {
+            let pasted: String = pasted.replace("\r\n", "\n")
+                .chars()
+                .map(|c| if c.is_control() && !(multiline && c == '\n') {
+                    ' '
+                } else {
+                    c
+                })
+                .collect();
+
+            self.replace_selection(text, &pasted);
+            edit.changed = true;
+        }
+
+        edit.moved = edit.changed || *self != before;
+
+        edit
+    }
+
+    /// Moves the caret to the grapheme boundary closest to `x`, which is
+    /// measured from the start of `text`. `width` gives how wide a piece of
+    /// text is when drawn.
+    pub(crate) fn click(
+        &mut self,
+        text: &str,
+        x: f32,
+        width: &dyn Fn(&str) -> f32,
+        extend_selection: bool,
+    ) {
+        let mut closest = (0, x.abs());
+
+        for (start, grapheme) in text.grapheme_indices(true) {
+            let end = start + grapheme.len();
+            let distance = (width(&text[..end]) - x).abs();
+            if distance < closest.1 {
+                closest = (end, distance);
+            }
+        }
+
+        self.move_to(closest.0, extend_selection);
+    }
+}
+
+#[cfg(test)]
+fn edited(text: &str, mut cursor: Cursor, input: TextInput) -> (String, Cursor, Edit) {
+    let mut text = text.to_owned();
+    let edit = cursor.edit(&mut text, &input, false);
+    (text, cursor, edit)
+}
+
+#[cfg(test)]
+fn typed(s: &str) -> TextInput {
+    let mut input = TextInput::default();
+    for (slot, c) in input.chars.iter_mut().zip(s.chars()) {
+        *slot = c;
+    }
+    input
+}
+
+#[cfg(test)]
+fn keys(keys: TextKeys) -> TextInput {
+    TextInput {
+        keys,
+        ..<_>::default()
+    }
+}
+
+#[test]
+fn typing_goes_in_at_the_caret_and_replaces_the_selection() {
+    let (text, cursor, _) = edited("ac", Cursor::at(1), typed("bλ"));
+    assert_eq!(text, "abλc");
+    assert_eq!(cursor, Cursor::at(4));
+
+    let (text, cursor, _) = edited("a123d", Cursor { caret: 1, anchor: 4 }, typed("é"));
+    assert_eq!(text, "aéd");
+    assert_eq!(cursor, Cursor::at(3));
+}
+
+#[test]
+fn backspace_and_delete_remove_a_whole_grapheme_cluster() {
+    // An "e" followed by a combining acute accent, then a family emoji
+    // made of several people joined together.
+    let family = "\u{1F468}\u{200D}\u{1F469}\u{200D}\u{1F467}";
+    let text = format!("cafe\u{301}{family}");
+
+    let (after, _, _) = edited(&text, Cursor::at(text.len()), typed("\u{8}"));
+    assert_eq!(after, "cafe\u{301}");
+
+    let (after, _, _) = edited(&text, Cursor::at(3), typed("\u{7f}"));
+    assert_eq!(after, format!("caf{family}"));
+
+    let (after, _, edit) = edited("", Cursor::at(0), typed("\u{8}\u{7f}\t"));
+    assert_eq!(after, "");
+    assert!(!edit.changed);
+}
+
+#[test]
+fn moving_goes_by_grapheme_or_word_and_shift_selects() {
+    let text = "one  two\u{301} three";
+
+    let (_, cursor, _) = edited(text, Cursor::at(7), keys(TEXT_RIGHT));
+    assert_eq!(cursor, Cursor::at(10));
+
+    let (_, cursor, _) = edited(text, Cursor::at(text.len()), keys(TEXT_LEFT | TEXT_CTRL_DOWN));
+    assert_eq!(cursor, Cursor::at(11));
+
+    let (_, cursor, _) = edited(text, Cursor::at(3), keys(TEXT_RIGHT | TEXT_CTRL_DOWN));
+    assert_eq!(cursor, Cursor::at(10));
+
+    let (_, cursor, _) = edited(text, Cursor::at(5), keys(TEXT_END | TEXT_SHIFT_DOWN));
+    assert_eq!(cursor, Cursor { caret: text.len(), anchor: 5 });
+
+    // Moving without shift from a selection goes to that end of it.
+    let (_, cursor, _) = edited(text, Cursor { caret: 10, anchor: 2 }, keys(TEXT_LEFT));
+    assert_eq!(cursor, Cursor::at(2));
+}
+
+#[test]
+fn ctrl_backspace_and_delete_remove_a_word() {
+    let (text, cursor, _) = edited(
+        "check this  pair",
+        Cursor::at(12),
+        TextInput {
+            keys: TEXT_CTRL_DOWN,
+            ..typed("\u{8}")
+        },
+    );
+    assert_eq!(text, "check pair");
+    assert_eq!(cursor, Cursor::at(6));
+
+    let (text, _, _) = edited(
+        "check this pair",
+        Cursor::at(5),
+        TextInput {
+            keys: TEXT_CTRL_DOWN,
+            ..typed("\u{7f}")
+        },
+    );
+    assert_eq!(text, "check pair");
+}
+
+#[test]
+fn cutting_and_pasting_go_through_the_selection() {
+    let (text, cursor, edit) = edited(
+        "abcd",
+        Cursor::at(0),
+        keys(TEXT_SELECT_ALL | TEXT_COPY),
+    );
+    assert_eq!(text, "abcd");
+    assert_eq!(cursor, Cursor { caret: 4, anchor: 0 });
+    assert_eq!(edit.copied.as_deref(), Some("abcd"));
+
+    let (text, cursor, edit) = edited("abcd", Cursor { caret: 1, anchor: 3 }, keys(TEXT_CUT));
+    assert_eq!(text, "ad");
+    assert_eq!(cursor, Cursor::at(1));
+    assert_eq!(edit.copied.as_deref(), Some("bc"));
+
+    let (text, cursor, _) = edited(
+        "ad",
+        Cursor::at(1),
+        TextInput {
+            pasted: Some("b\nc".to_owned()),
+            ..<_>::default()
+        },
+    );
+    assert_eq!(text, "ab cd");
+    assert_eq!(cursor, Cursor::at(4));
+}
+
+#[test]
+fn clicking_puts_the_caret_at_the_closest_boundary() {
+    // Every `char` is 10 wide.
+    let width = |s: &str| s.chars().count() as f32 * 10.;
+    let text = "ae\u{301}b";
+
+    let mut cursor = Cursor::at(0);
+    cursor.click(text, 14., &width, false);
+    // The accent is drawn on top of the e, but the caret cannot go between
+    // them.
+    assert_eq!(cursor, Cursor::at(1));
+
+    cursor.click(text, 100., &width, true);
+    assert_eq!(cursor, Cursor { caret: text.len(), anchor: 1 });
+}
+
+#[test]
+fn multiline_text_keeps_enter_and_pasted_newlines() {
+    let mut text = "ab".to_owned();
+    let mut cursor = Cursor::at(1);
+
+    let edit = cursor.edit(&mut text, &keys(TEXT_ENTER), true);
+    assert!(edit.changed);
+    assert_eq!(text, "a\nb");
+    assert_eq!(cursor, Cursor::at(2));
+
+    cursor.edit(
+        &mut text,
+        &TextInput {
+            pasted: Some("c\r\nd\te".to_owned()),
+            ..<_>::default()
+        },
+        true,
+    );
+    assert_eq!(text, "a\nc\nd eb");
+
+    // Without `multiline`, enter is left for whatever is editing the text.
+    let (text, _, edit) = edited("ab", Cursor::at(1), keys(TEXT_ENTER));
+    assert_eq!(text, "ab");
+    assert!(!edit.changed);
+}
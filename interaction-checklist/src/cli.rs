@@ -0,0 +1,567 @@
+use std::path::PathBuf;
+
+pub const USAGE: &str = "\
+usage: interaction-checklist [OPTIONS] [PATH]
+       interaction-checklist COMMAND PATH [ARGS]
+
+Opens the board at PATH, (default: interaction-checklist.board). The board is
+saved back to PATH when the window is closed, or when Ctrl+S is pressed. A
+missing PATH is an error, unless --new is passed, or PATH is the default.
+
+options:
+    --new               Create a new board at PATH instead of opening one.
+                        It is an error if PATH already exists.
+    --labels A,B,C      Set both the column and the row labels to the given
+                        comma separated list.
+    --columns A,B,C     Set the column labels, (along the top.)
+    --rows A,B,C        Set the row labels, (along the left side.)
+    --count N           Make a board with N empty column and row labels.
+                        Boards have between 3 and 200 labels on each axis.
+    --labels-from FILE  Like --labels, but with one label per line of FILE.
+                        Pass - to read them from standard input.
+    --columns-from FILE Like --columns, but with one label per line of FILE.
+    --rows-from FILE    Like --rows, but with one label per line of FILE.
+    --features-from MANIFEST
+                        Use the features in the [features] table of a
+                        Cargo.toml file, or the one in a directory, as both
+                        the column and the row labels, leaving out default.
+                        New boards made this way are symmetric, unless
+                        --symmetry says otherwise.
+    --optional-deps     With --features-from, also use the optional
+                        dependencies that Cargo makes features for.
+    --trim              Remove whitespace from the start and end of each
+                        label read from a file.
+    --skip-comments     Skip lines in label files that start with #.
+    --skip-blank        Skip empty lines in label files, instead of making
+                        them empty labels.
+    --drop-extra-labels Use as many labels as fit on the board, and only warn
+                        about the rest, instead of stopping.
+    --symmetry MODE     Set whether the tile in column A, row B is the same as
+                        the one in column B, row A. MODE is one of:
+                        off: They are separate tiles.
+                        dimmed: They are the same, and the mirrored
+                                triangle is dimmed.
+                        hidden: They are the same, and the mirrored
+                                triangle is hidden.
+                        Only boards with as many columns as rows can be
+                        symmetric. M cycles between the modes in the window.
+    --triangle WHICH    Keep the upper or lower triangle of a symmetric board,
+                        with the other one mirroring it. Defaults to lower.
+                        T swaps the triangles in the window.
+    --diagonal MODE     Set how the diagonal of a symmetric board, where each
+                        label is paired with itself, is handled. MODE is one
+                        of included, excluded or not-applicable. Excluded and
+                        not applicable tiles are not counted, and only not
+                        applicable tiles are shown. Defaults to included.
+                        I cycles between the modes in the window.
+    --eye-movement MODE Set how the eye moves while the arrow keys or WASD are
+                        held. MODE is one of repeating, where it keeps moving
+                        after a short delay, or per-press, where it moves one
+                        tile for each key press. Defaults to repeating.
+    --key-repeat D,I    Set how long a key is held before the eye starts
+                        repeating, (D,) and how long between each repeat, (I,)
+                        in frames, which are a 60th of a second. Defaults to
+                        18,4. Implies --eye-movement repeating, so it
+                        cannot be used with --eye-movement per-press.
+    --windowed          Start in a window instead of fullscreen.
+    --size WxH          Start with a window of the given size, in pixels.
+                        Implies --windowed.
+    -h, --help          Print this message.
+
+With --new, the board gets one column per column label, and one row per row
+label. The labels for each axis come from the first of --columns or --rows,
+--labels, then --count that is passed. With none of those, there are 16.
+Without --new, the given labels replace the board's labels from the start,
+and it is an error to give more labels than the board has lines for. Each
+--*-from option takes the place of the matching option without -from.
+
+commands, which work on the board at PATH without opening a window:
+    A command must be the first argument, before any options or PATH.
+    status              Print how many tiles are checked, out of the total,
+                        then how many tiles have each status. Mirrored tiles
+                        on symmetric boards count once.
+    list-unchecked      Print the pair of labels and the status of each tile
+                        that still needs checking, one tile per line,
+                        separated by tabs.
+    check A B           Mark the tile in column A and row B as passing.
+    uncheck A B         Mark the tile in column A and row B as unchecked.
+    set A B STATUS      Set the status of the tile in column A and row B.
+    note A B [TEXT]     Print the note on the tile in column A and row B, or
+                        set it to TEXT if given. An empty TEXT removes it.
+    set-label [--columns|--rows] N TEXT
+                        Set label number N, counting from 0, to TEXT. Sets
+                        both the column and row label unless an axis is given.
+    sync-enum [--columns|--rows] FILE ENUM
+                        Set the column or row labels to the names of the
+                        variants of the enum named ENUM in the Rust source
+                        file FILE, or both if no axis is given. Lines for
+                        variants that are still there keep their tiles, and
+                        move to match the order of the variants. Lines for
+                        variants that are gone are removed, and new variants
+                        get new lines, so it can be run again whenever the
+                        enum changes. Creates the board if PATH does not
+                        exist.
+    report [--html] [FILE]
+                        Print a Markdown report of the board, with the
+                        progress, a table of the statuses with the progress of
+                        each row, and the notes. --html makes a standalone
+                        HTML page with coloured cells instead. With FILE, the
+                        report is written there instead of printed. Ctrl+R
+                        in the window writes both next to the board file.
+    export-csv [CSV]    Write the labels and the status of each tile as a CSV
+                        matrix, for spreadsheets, to the file CSV, or print it
+                        if no file is given. Notes are left out.
+    import-csv CSV      Replace the labels and statuses of the board with the
+                        ones in the CSV file, in the format export-csv writes,
+                        creating the board if PATH does not exist. Notes on
+                        pairs that are still on the board are kept, and so is
+                        the symmetry, if the board is still square. On a
+                        symmetric board, it is an error for the two cells of
+                        a pair to both be checked, with different statuses.
+
+A and B can be either a label or a label number. Empty labels are printed as
+their number.
+
+The statuses are unchecked, passes, fails, blocked, needs-recheck and
+not-applicable, which is also the order clicking on a tile cycles through them
+in. Right clicking goes through them backwards. Passes, fails and
+not-applicable count as checked.
+";
+
+pub type WindowLength = i32;
+
+#[derive(Debug, Default)]
+pub struct Window {
+    pub windowed: bool,
+    pub size: Option<(WindowLength, WindowLength)>,
+}
+
+#[derive(Debug)]
+pub struct Args {
+    /// `None` means use the default path.
+    pub path: Option<PathBuf>,
+    pub new: bool,
+    pub labels: Option<Vec<String>>,
+    pub column_labels: Option<Vec<String>>,
+    pub row_labels: Option<Vec<String>>,
+    pub count: Option<usize>,
+    pub labels_from: Option<PathBuf>,
+    pub column_labels_from: Option<PathBuf>,
+    pub row_labels_from: Option<PathBuf>,
+    pub features_from: Option<PathBuf>,
+    pub feature_options: app::cargo::FeatureOptions,
+    pub label_list: app::LabelListOptions,
+    pub drop_extra_labels: bool,
+    pub symmetry: Option<SymmetryArg>,
+    pub triangle: Option<app::Triangle>,
+    pub diagonal: Option<app::Diagonal>,
+    pub eye_movement: Option<app::EyeMovement>,
+    pub window: Window,
+}
+
+/// Separate from `app::Symmetry` so that turning symmetry on keeps the rest of
+/// the layout of boards that are already symmetric.
+#[derive(Debug)]
+pub enum SymmetryArg {
+    Off,
+    On(app::MirrorTiles),
+}
+
+#[derive(Debug)]
+pub enum Command {
+    Status,
+    ListUnchecked,
+    Set { column: String, row: String, status: app::Status },
+    /// `None` means print the note instead of setting it.
+    Note { column: String, row: String, text: Option<String> },
+    /// `None` means both axes.
+    SetLabel { axis: Option<app::Axis>, index: usize, text: String },
+    /// `None` means both axes.
+    SyncEnum { axis: Option<app::Axis>, source: PathBuf, name: String },
+    /// `None` means print the report instead of writing it to a file.
+    Report { html: bool, output: Option<PathBuf> },
+    /// `None` means print the CSV instead of writing it to a file.
+    ExportCsv { output: Option<PathBuf> },
+    ImportCsv { input: PathBuf },
+}
+
+/// The names of the commands that work without opening a window.
+const COMMANDS: &[&str] = &[
+    "status",
+    "list-unchecked",
+    "check",
+    "uncheck",
+    "set",
+    "note",
+    "set-label",
+    "sync-enum",
+    "report",
+    "export-csv",
+    "import-csv",
+];
+
+#[derive(Debug)]
+pub enum Parsed {
+    Run(Args),
+    Headless(PathBuf, Command),
+    Help,
+}
+
+/// Takes `--columns` or `--rows` off the front of `args`, if it is there.
+fn axis_option(
+    args: &mut std::iter::Peekable<impl Iterator<Item = String>>,
+) -> Option<app::Axis> {
+    let axis = match args.peek().map(String::as_str) {
+        Some("--columns") => Some(app::Axis::Columns),
+        Some("--rows") => Some(app::Axis::Rows),
+        _ => None,
+    };
+    if axis.is_some() {
+        args.next();
+    }
+
+    axis
+}
+
+fn parse_command(
+    name: &str,
+    args: impl Iterator<Item = String>,
+) -> Result<Parsed, String> {
+    let mut args = args.peekable();
+
+    macro_rules! next {
+        ($what: literal) => {
+            args.next().ok_or_else(|| format!("{name} needs {}", $what))?
+        }
+    }
+
+    let path = PathBuf::from(next!("a board path"));
+
+    let command = match name {
+        "status" => Command::Status,
+        "list-unchecked" => Command::ListUnchecked,
+        "check" | "uncheck" => Command::Set {
+            column: next!("a column"),
+            row: next!("a row"),
+            status: if name == "check" {
+                app::Status::Passes
+            } else {
+                app::Status::Unchecked
+            },
+        },
+        "set" => {
+            let column = next!("a column");
+            let row = next!("a row");
+            let status = next!("a status");
+
+            Command::Set {
+                column,
+                row,
+                status: app::Status::from_name(&status).ok_or_else(|| {
+                    format!("unknown status \"{status}\"")
+                })?,
+            }
+        },
+        "note" => Command::Note {
+            column: next!("a column"),
+            row: next!("a row"),
+            text: args.next(),
+        },
+        "set-label" => {
+            let axis = axis_option(&mut args);
+
+            let index = next!("a label number");
+            Command::SetLabel {
+                axis,
+                index: index.parse().map_err(|_| {
+                    format!("expected a label number, not \"{index}\"")
+                })?,
+                text: next!("the label text"),
+            }
+        },
+        "sync-enum" => Command::SyncEnum {
+            axis: axis_option(&mut args),
+            source: PathBuf::from(next!("a Rust source file")),
+            name: next!("an enum name"),
+        },
+        "report" => {
+            let html = args.next_if(|arg| arg == "--html").is_some();
+
+            Command::Report {
+                html,
+                output: args.next().map(PathBuf::from),
+            }
+        },
+        "export-csv" => Command::ExportCsv {
+            output: args.next().map(PathBuf::from),
+        },
+        "import-csv" => Command::ImportCsv {
+            input: PathBuf::from(next!("a CSV file")),
+        },
+        _ => unreachable!("parse_command called with unknown command {name}"),
+    };
+
+    if let Some(extra) = args.next() {
+        return Err(format!("unexpected extra argument \"{extra}\""));
+    }
+
+    Ok(Parsed::Headless(path, command))
+}
+
+fn parse_labels(s: &str) -> Vec<String> {
+    s.split(',').map(str::to_owned).collect()
+}
+
+fn parse_size(s: &str) -> Option<(WindowLength, WindowLength)> {
+    let (w, h) = s.split_once(['x', 'X'])?;
+
+    let w = w.parse().ok().filter(|&w| w > 0)?;
+    let h = h.parse().ok().filter(|&h| h > 0)?;
+
+    Some((w, h))
+}
+
+fn parse_key_repeat(s: &str) -> Option<app::KeyRepeat> {
+    let (delay, interval) = s.split_once(',')?;
+
+    Some(app::KeyRepeat {
+        delay: delay.trim().parse().ok()?,
+        interval: interval.trim().parse().ok()?,
+    })
+}
+
+pub fn parse(args: impl IntoIterator<Item = String>) -> Result<Parsed, String> {
+    let mut path = None;
+    let mut new = false;
+    let mut labels = None;
+    let mut column_labels = None;
+    let mut row_labels = None;
+    let mut count = None;
+    let mut labels_from = None;
+    let mut column_labels_from = None;
+    let mut row_labels_from = None;
+    let mut features_from = None;
+    let mut feature_options = app::cargo::FeatureOptions::default();
+    let mut label_list = app::LabelListOptions::default();
+    let mut drop_extra_labels = false;
+    let mut symmetry = None;
+    let mut triangle = None;
+    let mut diagonal = None;
+    let mut eye_movement = None;
+    let mut key_repeat = None;
+    let mut window = Window::default();
+
+    let mut args = args.into_iter().peekable();
+
+    if let Some(name) = args.next_if(|arg| COMMANDS.contains(&arg.as_str())) {
+        return parse_command(&name, args);
+    }
+
+    while let Some(arg) = args.next() {
+        macro_rules! value {
+            () => {
+                args.next().ok_or_else(|| format!("{arg} needs a value"))?
+            }
+        }
+
+        match arg.as_str() {
+            "-h" | "--help" => return Ok(Parsed::Help),
+            "--new" => { new = true; },
+            "--labels" => { labels = Some(parse_labels(&value!())); },
+            "--columns" => { column_labels = Some(parse_labels(&value!())); },
+            "--rows" => { row_labels = Some(parse_labels(&value!())); },
+            "--count" => {
+                let value = value!();
+                count = Some(value.parse().map_err(|_| {
+                    format!("expected a number of labels, not \"{value}\"")
+                })?);
+            },
+            "--labels-from" => { labels_from = Some(PathBuf::from(value!())); },
+            "--columns-from" => { column_labels_from = Some(PathBuf::from(value!())); },
+            "--rows-from" => { row_labels_from = Some(PathBuf::from(value!())); },
+            "--features-from" => { features_from = Some(PathBuf::from(value!())); },
+            "--optional-deps" => { feature_options.optional_dependencies = true; },
+            "--trim" => { label_list.trim = true; },
+            "--skip-comments" => { label_list.skip_comments = true; },
+            "--skip-blank" => { label_list.skip_blank = true; },
+            "--drop-extra-labels" => { drop_extra_labels = true; },
+            "--symmetry" => {
+                use app::MirrorTiles;
+                symmetry = Some(match value!().as_str() {
+                    "off" => SymmetryArg::Off,
+                    "dimmed" => SymmetryArg::On(MirrorTiles::Dimmed),
+                    "hidden" => SymmetryArg::On(MirrorTiles::Hidden),
+                    other => return Err(format!(
+                        "expected off, dimmed or hidden, not \"{other}\""
+                    )),
+                });
+            },
+            "--triangle" => {
+                triangle = Some(match value!().as_str() {
+                    "upper" => app::Triangle::Upper,
+                    "lower" => app::Triangle::Lower,
+                    other => return Err(format!(
+                        "expected upper or lower, not \"{other}\""
+                    )),
+                });
+            },
+            "--diagonal" => {
+                use app::Diagonal;
+                diagonal = Some(match value!().as_str() {
+                    "included" => Diagonal::Included,
+                    "excluded" => Diagonal::Excluded,
+                    "not-applicable" => Diagonal::NotApplicable,
+                    other => return Err(format!(
+                        "expected included, excluded or not-applicable, not \"{other}\""
+                    )),
+                });
+            },
+            "--eye-movement" => {
+                use app::EyeMovement;
+                if eye_movement.is_some() {
+                    return Err(format!("{arg} was given more than once"));
+                }
+                eye_movement = Some(match value!().as_str() {
+                    "repeating" => EyeMovement::Repeating(<_>::default()),
+                    "per-press" => EyeMovement::PerPress,
+                    other => return Err(format!(
+                        "expected repeating or per-press, not \"{other}\""
+                    )),
+                });
+            },
+            "--key-repeat" => {
+                if key_repeat.is_some() {
+                    return Err(format!("{arg} was given more than once"));
+                }
+                let value = value!();
+                key_repeat = Some(parse_key_repeat(&value).ok_or_else(|| {
+                    format!("expected two frame counts like 18,4, not \"{value}\"")
+                })?);
+            },
+            "--windowed" => { window.windowed = true; },
+            "--size" => {
+                let value = value!();
+                window.windowed = true;
+                window.size = Some(parse_size(&value).ok_or_else(|| {
+                    format!("expected a size like 1280x720, not \"{value}\"")
+                })?);
+            },
+            _ if arg.starts_with('-') => {
+                return Err(format!("unknown option \"{arg}\""));
+            },
+            _ if COMMANDS.contains(&arg.as_str()) => {
+                return Err(format!(
+                    "{arg} is a command, so it must come before any options"
+                ));
+            },
+            _ => {
+                if path.is_some() {
+                    return Err(format!("unexpected extra argument \"{arg}\""));
+                }
+                path = Some(PathBuf::from(arg));
+            },
+        }
+    }
+
+    let eye_movement = match (eye_movement, key_repeat) {
+        (Some(app::EyeMovement::PerPress), Some(_)) => {
+            return Err(
+                "--key-repeat cannot be used with --eye-movement per-press"
+                    .to_owned()
+            );
+        },
+        (_, Some(key_repeat)) => Some(app::EyeMovement::Repeating(key_repeat)),
+        (eye_movement, None) => eye_movement,
+    };
+
+    Ok(Parsed::Run(Args {
+        path,
+        new,
+        labels,
+        column_labels,
+        row_labels,
+        count,
+        labels_from,
+        column_labels_from,
+        row_labels_from,
+        features_from,
+        feature_options,
+        label_list,
+        drop_extra_labels,
+        symmetry,
+        triangle,
+        diagonal,
+        eye_movement,
+        window,
+    }))
+}
+
+#[cfg(test)]
+fn parse_str(args: &str) -> Result<Parsed, String> {
+    parse(args.split_whitespace().map(str::to_owned))
+}
+
+#[test]
+fn options_and_the_path_are_parsed_in_any_order() {
+    let Ok(Parsed::Run(args)) = parse_str(
+        "--windowed board --columns a,b --key-repeat 10,2 --new"
+    ) else {
+        panic!("expected options to run the window with");
+    };
+
+    assert_eq!(args.path, Some(PathBuf::from("board")));
+    assert!(args.new && args.window.windowed);
+    assert_eq!(args.column_labels, Some(vec!["a".to_owned(), "b".to_owned()]));
+    assert_eq!(
+        args.eye_movement,
+        Some(app::EyeMovement::Repeating(app::KeyRepeat { delay: 10, interval: 2 }))
+    );
+
+    let Ok(Parsed::Run(args)) = parse_str("--size 800x600 board") else {
+        panic!("expected a size to run the window with");
+    };
+
+    assert!(args.window.windowed);
+    assert_eq!(args.window.size, Some((800, 600)));
+
+    assert!(matches!(parse_str("--help board"), Ok(Parsed::Help)));
+    assert!(parse_str("--count").is_err());
+    assert!(parse_str("--bogus").is_err());
+    assert!(parse_str("one two").is_err());
+}
+
+#[test]
+fn commands_are_parsed_with_their_arguments() {
+    assert!(matches!(
+        parse_str("set-label board --rows 2 text"),
+        Ok(Parsed::Headless(_, Command::SetLabel {
+            axis: Some(app::Axis::Rows),
+            index: 2,
+            ..
+        }))
+    ));
+    assert!(matches!(
+        parse_str("set board a b fails"),
+        Ok(Parsed::Headless(_, Command::Set { status: app::Status::Fails, .. }))
+    ));
+    assert!(parse_str("set board a b maybe").is_err());
+    assert!(parse_str("check board a").is_err());
+    assert!(parse_str("status board extra").is_err());
+}
+
+#[test]
+fn conflicting_eye_movement_options_are_rejected() {
+    assert!(parse_str("--eye-movement per-press --key-repeat 18,4").is_err());
+    assert!(parse_str("--key-repeat 18,4 --eye-movement per-press").is_err());
+    assert!(parse_str("--eye-movement repeating --eye-movement per-press").is_err());
+    assert!(parse_str("--key-repeat 18,4 --key-repeat 9,2").is_err());
+    assert!(parse_str("--eye-movement repeating --key-repeat 18,4").is_ok());
+}
+
+#[test]
+fn commands_after_options_are_rejected() {
+    let error = parse_str("--windowed status board").unwrap_err();
+
+    assert!(error.contains("status"), "{error}");
+    assert!(parse_str("board status").is_err());
+}
@@ -0,0 +1,217 @@
+//! Running commands on boards without opening a window, for use in scripts.
+
+use crate::{cli::Command, load_state, report_title, save_state};
+use app::{Axis, Board};
+use std::path::Path;
+
+fn axis_name(axis: Axis) -> &'static str {
+    match axis {
+        Axis::Columns => "column",
+        Axis::Rows => "row",
+    }
+}
+
+/// Accepts either the text of a label, or a label number.
+fn resolve_label(board: &Board, axis: Axis, name: &str) -> Result<usize, String> {
+    board.label_index(axis, name)
+        .or_else(|| {
+            name.parse::<usize>()
+                .ok()
+                .filter(|&i| i < board.labels(axis).len())
+        })
+        .ok_or_else(|| format!("no {} label \"{name}\" on the board", axis_name(axis)))
+}
+
+fn display_label(board: &Board, axis: Axis, i: usize) -> String {
+    match board.labels(axis).get(i) {
+        Some(label) if !label.is_empty() => label.clone(),
+        _ => i.to_string(),
+    }
+}
+
+pub fn run(path: &Path, command: Command) -> Result<(), String> {
+    let mut state = match (load_state(path)?, &command) {
+        (Some(state), _) => state,
+        // Importing makes a whole board, so there does not need to be one
+        // already.
+        (None, Command::ImportCsv { .. }) => app::State::default(),
+        // Start as small as possible, so the lines that are not from the
+        // enum are easy to replace.
+        (None, Command::SyncEnum { .. }) => app::State::from(
+            Board::with_labels(vec![String::new(); app::LABEL_COUNT_MIN])
+                .expect("the fewest labels should be allowed")
+        ),
+        (None, _) => return Err(format!("{} does not exist", path.display())),
+    };
+
+    let board = state.board_mut();
+
+    match command {
+        Command::Status => {
+            let progress = board.progress();
+            println!("{}/{} checked", progress.checked, progress.total);
+            for (status, count) in progress.counts.iter() {
+                println!("{}\t{count}", status.name());
+            }
+
+            return Ok(());
+        },
+        Command::ListUnchecked => {
+            use std::io::Write;
+            let mut stdout = std::io::stdout().lock();
+
+            for (column, row, status) in board.unchecked_pairs() {
+                let written = writeln!(
+                    stdout,
+                    "{}\t{}\t{}",
+                    display_label(board, Axis::Columns, column),
+                    display_label(board, Axis::Rows, row),
+                    status.name(),
+                );
+
+                // Stop quietly if stdout was closed, for example when piping
+                // into `head`.
+                if written.is_err() {
+                    break;
+                }
+            }
+
+            return Ok(());
+        },
+        Command::Set { column, row, status } => {
+            let column = resolve_label(board, Axis::Columns, &column)?;
+            let row = resolve_label(board, Axis::Rows, &row)?;
+
+            board.set_status(column, row, status)
+                .map_err(|_| "pair is outside the board".to_string())?;
+        },
+        Command::Note { column, row, text } => {
+            let column = resolve_label(board, Axis::Columns, &column)?;
+            let row = resolve_label(board, Axis::Rows, &row)?;
+
+            let Some(text) = text else {
+                let note = board.note(column, row)
+                    .map_err(|_| "pair is outside the board".to_string())?;
+                if !note.is_empty() {
+                    println!("{note}");
+                }
+
+                return Ok(());
+            };
+
+            board.set_note(column, row, text)
+                .map_err(|_| "pair is outside the board".to_string())?;
+        },
+        Command::SetLabel { axis, index, text } => {
+            let axes = match axis {
+                Some(axis) => vec![axis],
+                None => vec![Axis::Columns, Axis::Rows],
+            };
+
+            // Check every axis first, so nothing is changed on an error.
+            for &axis in axes.iter() {
+                let count = board.labels(axis).len();
+                if index >= count {
+                    return Err(format!(
+                        "{} label number {index} is out of range (0 to {})",
+                        axis_name(axis),
+                        count - 1
+                    ));
+                }
+            }
+
+            for axis in axes {
+                board.labels_mut(axis)[index].clone_from(&text);
+            }
+        },
+        Command::SyncEnum { axis, source, name } => {
+            let text = std::fs::read_to_string(&source)
+                .map_err(|e| format!("{}: {e}", source.display()))?;
+            let labels = app::rust_enum::variant_labels(&text, &name)
+                .map_err(|e| format!("{}: {e}", source.display()))?;
+
+            let axes = match axis {
+                Some(axis) => vec![axis],
+                None => vec![Axis::Columns, Axis::Rows],
+            };
+
+            for axis in axes {
+                board.sync_labels(axis, &labels)
+                    .map_err(|e| format!("could not sync the {} labels: {e}", axis_name(axis)))?;
+            }
+        },
+        Command::Report { html, output } => {
+            let title = report_title(path);
+            let report = if html {
+                app::report::html(board, &title)
+            } else {
+                app::report::markdown(board, &title)
+            };
+
+            match output {
+                Some(output) => std::fs::write(&output, report)
+                    .map_err(|e| format!("Could not write {}: {e}", output.display()))?,
+                None => print!("{report}"),
+            }
+
+            return Ok(());
+        },
+        Command::ExportCsv { output } => {
+            let csv = app::csv::write(board);
+
+            match output {
+                Some(output) => std::fs::write(&output, csv)
+                    .map_err(|e| format!("Could not write {}: {e}", output.display()))?,
+                None => print!("{csv}"),
+            }
+
+            return Ok(());
+        },
+        Command::ImportCsv { input } => {
+            let text = std::fs::read_to_string(&input)
+                .map_err(|e| format!("{}: {e}", input.display()))?;
+            let mut imported = app::csv::read(&text)
+                .map_err(|e| format!("{}: {e}", input.display()))?;
+
+            if let app::Symmetry::Symmetric(_) = board.symmetry() {
+                // Making the board symmetric would silently pick one of each
+                // of these pairs.
+                let conflicts: Vec<String> = imported.conflicting_mirrors()
+                    .map(|(column, row)| format!(
+                        "{} and {}",
+                        display_label(&imported, Axis::Columns, column),
+                        display_label(&imported, Axis::Rows, row),
+                    ))
+                    .collect();
+                if !conflicts.is_empty() {
+                    return Err(format!(
+                        "{}: the board is symmetric, but these pairs have a different status in each triangle: {}",
+                        input.display(),
+                        conflicts.join(", "),
+                    ));
+                }
+
+                if let Err(app::NotSquare) = imported.set_symmetry(board.symmetry()) {
+                    eprintln!(
+                        "Warning: {} does not have as many columns as rows, so the board is not symmetric any more",
+                        input.display(),
+                    );
+                }
+            }
+
+            for (column, row, note) in board.notes() {
+                let pair = imported.label_index(Axis::Columns, &board.labels(Axis::Columns)[column])
+                    .zip(imported.label_index(Axis::Rows, &board.labels(Axis::Rows)[row]));
+
+                if let Some((column, row)) = pair {
+                    let _ = imported.set_note(column, row, note.to_owned());
+                }
+            }
+
+            *board = imported;
+        },
+    }
+
+    save_state(path, &state)
+        .map_err(|e| format!("Could not save {}: {e}", path.display()))
+}
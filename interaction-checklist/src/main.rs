@@ -20,6 +20,23 @@ const SAMPLING_SHADER: &str = include_str!("../assets/sampling.fs");
 
 const SPRITESHEET_BYTES: &[u8] = include_bytes!("../assets/spritesheet.png");
 
+const FONT_BYTES: &[u8] = include_bytes!("../assets/DejaVuSans.ttf");
+
+/// The characters that always have glyphs loaded. Any others are added once
+/// they show up in a label or a note.
+const BASE_GLYPH_RANGES: [core::ops::RangeInclusive<char>; 5] = [
+    // ASCII, without the control characters.
+    ' '..='~',
+    // Latin-1 Supplement, Latin Extended-A and Latin Extended-B.
+    '\u{a0}'..='\u{24f}',
+    // Greek and Coptic, and Cyrillic.
+    '\u{370}'..='\u{4ff}',
+    // General Punctuation.
+    '\u{2000}'..='\u{206f}',
+    // Arrows.
+    '\u{2190}'..='\u{21ff}',
+];
+
 const SPRITE_PIXELS_PER_TILE_SIDE: f32 = 16.0;
 
 use app::{SpriteKind, ArrowKind, Dir};
@@ -46,7 +63,12 @@ fn source_spec(sprite: SpriteKind) -> SourceSpec {
         | HalfLidEye => 0.,
         Arrow(_, Green)| DirEye(_) => 1.,
         Unchecked(_) => 2.,
-        Checked(_) => 3.,
+        Passes(_) => 3.,
+        NotApplicable(_) => 4.,
+        Fails(_) => 5.,
+        Blocked(_) => 6.,
+        NeedsRecheck(_) => 7.,
+        NoteMarker => 4.,
         NineSlice(
             NS::UpperLeft | NS::Left | NS::LowerLeft,
             WhiteEdge
@@ -77,6 +99,21 @@ fn source_spec(sprite: SpriteKind) -> SourceSpec {
         LRThreeSlice(LRTS::Left, YellowEdge) => 5.,
         LRThreeSlice(LRTS::Center, YellowEdge) => 6.,
         LRThreeSlice(LRTS::Right, YellowEdge) => 7.,
+        NineSlice(
+            NS::UpperLeft | NS::Left | NS::LowerLeft,
+            BlueEdge
+        ) => 8.,
+        NineSlice(
+            NS::Upper | NS::NoEdges | NS::Lower,
+            BlueEdge
+        ) => 9.,
+        NineSlice(
+            NS::UpperRight | NS::Right | NS::LowerRight,
+            BlueEdge
+        ) => 10.,
+        LRThreeSlice(LRTS::Left, BlueEdge) => 8.,
+        LRThreeSlice(LRTS::Center, BlueEdge) => 9.,
+        LRThreeSlice(LRTS::Right, BlueEdge) => 10.,
     };
 
     let sy = match sprite {
@@ -103,9 +140,17 @@ fn source_spec(sprite: SpriteKind) -> SourceSpec {
         NarrowRightEye => 12.,
         NarrowLeftEye => 13.,
         SmallPupilEye => 14.,
-        Unchecked(Idle) | Checked(Idle) => 0.,
-        Unchecked(Hover) | Checked(Hover) => 1.,
-        Unchecked(Pressed) | Checked(Pressed) => 2.,
+        NoteMarker => 3.,
+        Unchecked(ui_state)
+        | Passes(ui_state)
+        | Fails(ui_state)
+        | Blocked(ui_state)
+        | NeedsRecheck(ui_state)
+        | NotApplicable(ui_state) => match ui_state {
+            Idle => 0.,
+            Hover => 1.,
+            Pressed => 2.,
+        },
         NineSlice(
             NS::UpperLeft | NS::Upper | NS::UpperRight,
             _
@@ -129,8 +174,302 @@ fn source_spec(sprite: SpriteKind) -> SourceSpec {
 
 const WINDOW_TITLE: &str = "interaction-checklist";
 
+/// Where the board is loaded from at startup and saved to.
+const BOARD_PATH: &str = "interaction-checklist.board";
+
+use std::path::Path;
+
+/// Returns `Ok(None)` if there is no file at `path`.
+fn load_state(path: &Path) -> Result<Option<app::State>, String> {
+    match std::fs::read_to_string(path) {
+        Ok(text) => app::file::read(&text)
+            .map(|board| Some(app::State::from(board)))
+            .map_err(|e| format!("{}: {e}", path.display())),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(None),
+        Err(e) => Err(format!("{}: {e}", path.display())),
+    }
+}
+
+fn save_state(path: &Path, state: &app::State) -> std::io::Result<()> {
+    // Write to a separate file first, so that if something goes wrong
+    // part-way through, the previous save is still intact.
+    let temp_path = path.with_extension("tmp");
+    std::fs::write(&temp_path, app::file::write(state.board()))?;
+    std::fs::rename(&temp_path, path)
+}
+
+/// The title for reports on the board at `path`, which is the name of the
+/// file, without the extension.
+fn report_title(path: &Path) -> String {
+    path.file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_else(|| WINDOW_TITLE.to_owned())
+}
+
+/// Writes a Markdown and an HTML report next to the board file, with the same
+/// name but different extensions. Returns the Markdown report.
+fn write_reports(path: &Path, state: &app::State) -> std::io::Result<String> {
+    let title = report_title(path);
+
+    let markdown = app::report::markdown(state.board(), &title);
+    std::fs::write(path.with_extension("md"), &markdown)?;
+    std::fs::write(
+        path.with_extension("html"),
+        app::report::html(state.board(), &title),
+    )?;
+
+    Ok(markdown)
+}
+
+/// Describes labels that did not fit on the board, showing the first few.
+fn describe_extra_labels(extra: &[String], room: usize, axis: app::Axis) -> String {
+    const SHOWN: usize = 5;
+
+    let mut shown: Vec<_> = extra.iter()
+        .take(SHOWN)
+        .map(|label| format!("\"{label}\""))
+        .collect();
+    if extra.len() > SHOWN {
+        shown.push(format!("and {} more", extra.len() - SHOWN));
+    }
+
+    format!(
+        "{} labels did not fit on the board, which has room for {room} {}: {}.",
+        extra.len(),
+        match axis {
+            app::Axis::Columns => "columns",
+            app::Axis::Rows => "rows",
+        },
+        shown.join(", "),
+    )
+}
+
+mod cli;
+mod headless;
+
 fn main() {
-    raylib_rs_platform::inner_main();
+    let mut args = match cli::parse(std::env::args().skip(1)) {
+        Ok(cli::Parsed::Run(args)) => args,
+        Ok(cli::Parsed::Headless(path, command)) => {
+            if let Err(message) = headless::run(&path, command) {
+                eprintln!("{message}");
+                std::process::exit(1);
+            }
+            return;
+        },
+        Ok(cli::Parsed::Help) => {
+            print!("{}", cli::USAGE);
+            return;
+        },
+        Err(message) => {
+            eprintln!("{message}\n\n{}", cli::USAGE);
+            std::process::exit(2);
+        }
+    };
+
+    macro_rules! fail {
+        ($($format_args: tt)*) => {{
+            eprintln!($($format_args)*);
+            std::process::exit(1);
+        }}
+    }
+
+    // Read any label files first, so their labels are used the same way as
+    // ones passed directly.
+    {
+        use std::io::Read;
+
+        let mut read_stdin = false;
+
+        for (from, labels, option) in [
+            (args.labels_from.take(), &mut args.labels, "--labels"),
+            (args.column_labels_from.take(), &mut args.column_labels, "--columns"),
+            (args.row_labels_from.take(), &mut args.row_labels, "--rows"),
+        ] {
+            let Some(from) = from else {
+                continue
+            };
+
+            if labels.is_some() {
+                fail!("Pass only one of {option} and {option}-from.");
+            }
+
+            let text = if from.as_os_str() == "-" {
+                if read_stdin {
+                    fail!("Only one list of labels can be read from standard input.");
+                }
+                read_stdin = true;
+
+                let mut text = String::new();
+                if let Err(e) = std::io::stdin().read_to_string(&mut text) {
+                    fail!("Could not read labels from standard input: {e}");
+                }
+                text
+            } else {
+                match std::fs::read_to_string(&from) {
+                    Ok(text) => text,
+                    Err(e) => fail!("Could not read labels from {}: {e}", from.display()),
+                }
+            };
+
+            *labels = Some(app::parse_label_list(&text, args.label_list));
+        }
+
+        if let Some(mut manifest_path) = args.features_from.take() {
+            if args.labels.is_some() {
+                fail!("Pass only one of --labels, --labels-from and --features-from.");
+            }
+
+            if manifest_path.is_dir() {
+                manifest_path.push("Cargo.toml");
+            }
+
+            let manifest = match std::fs::read_to_string(&manifest_path) {
+                Ok(manifest) => manifest,
+                Err(e) => fail!("Could not read {}: {e}", manifest_path.display()),
+            };
+
+            match app::cargo::feature_labels(&manifest, args.feature_options) {
+                Ok(labels) => { args.labels = Some(labels); },
+                Err(e) => fail!("{}: {e}", manifest_path.display()),
+            }
+
+            // Checking a pair of features covers them both ways around.
+            if args.new && args.symmetry.is_none() {
+                args.symmetry = Some(cli::SymmetryArg::On(app::MirrorTiles::Dimmed));
+            }
+        }
+    }
+
+    let drop_extra_labels = args.drop_extra_labels;
+    let extra_labels = |message: String| {
+        if drop_extra_labels {
+            eprintln!("Warning: {message}");
+        } else {
+            fail!("{message} Pass --drop-extra-labels to use the ones that fit.");
+        }
+    };
+
+    let path = args.path.clone()
+        .unwrap_or_else(|| std::path::PathBuf::from(BOARD_PATH));
+
+    // Load before opening the window, so errors are easy to see.
+    let mut state = if args.new {
+        if path.exists() {
+            fail!("{} already exists. Leave off --new to open it.", path.display());
+        }
+
+        // The labels determine the size of new boards, so they are used up
+        // here.
+        let labels = args.labels.take();
+        let count = args.count.unwrap_or(app::LABEL_COUNT_DEFAULT);
+        let axis_labels = |axis, axis_labels: Option<Vec<String>>| {
+            let mut axis_labels = axis_labels
+                .or_else(|| labels.clone())
+                .unwrap_or_else(|| vec![String::new(); count]);
+
+            if axis_labels.len() > app::LABEL_COUNT_MAX {
+                let extra = axis_labels.split_off(app::LABEL_COUNT_MAX);
+                extra_labels(describe_extra_labels(&extra, app::LABEL_COUNT_MAX, axis));
+            }
+
+            axis_labels
+        };
+
+        let column_labels = axis_labels(app::Axis::Columns, args.column_labels.take());
+        let row_labels = axis_labels(app::Axis::Rows, args.row_labels.take());
+
+        match app::Board::with_axis_labels(column_labels, row_labels) {
+            Ok(board) => app::State::from(board),
+            Err(e) => fail!("Could not create board: {e}"),
+        }
+    } else {
+        if args.count.is_some() {
+            fail!("--count can only be used with --new.");
+        }
+
+        match load_state(&path) {
+            Ok(Some(state)) => state,
+            // Only the default path gets created implicitly, so that a typo
+            // in a given path doesn't silently start a fresh board.
+            Ok(None) if args.path.is_none() => app::State::default(),
+            Ok(None) => fail!(
+                "{} does not exist. Pass --new to create it.",
+                path.display()
+            ),
+            Err(message) => fail!("Could not load board: {message}"),
+        }
+    };
+
+    for (axis, labels) in [
+        (app::Axis::Columns, args.labels.as_ref()),
+        (app::Axis::Rows, args.labels.as_ref()),
+        (app::Axis::Columns, args.column_labels.as_ref()),
+        (app::Axis::Rows, args.row_labels.as_ref()),
+    ] {
+        let labels = match labels {
+            Some(labels) => labels,
+            None => continue,
+        };
+
+        let room = state.board().labels(axis).len();
+        let extra = state.board_mut().fill_labels(axis, labels);
+        if !extra.is_empty() {
+            extra_labels(describe_extra_labels(extra, room, axis));
+        }
+    }
+
+    if args.symmetry.is_some() || args.triangle.is_some() || args.diagonal.is_some() {
+        let mut layout = match state.board().symmetry() {
+            app::Symmetry::Asymmetric => None,
+            app::Symmetry::Symmetric(layout) => Some(layout),
+        };
+
+        match args.symmetry {
+            Some(cli::SymmetryArg::Off) => { layout = None; },
+            Some(cli::SymmetryArg::On(mirror_tiles)) => {
+                layout = Some(app::SymmetricLayout {
+                    mirror_tiles,
+                    ..layout.unwrap_or_default()
+                });
+            },
+            None => {},
+        }
+
+        let symmetry = match layout {
+            Some(mut layout) => {
+                if let Some(kept) = args.triangle {
+                    layout.kept = kept;
+                }
+                if let Some(diagonal) = args.diagonal {
+                    layout.diagonal = diagonal;
+                }
+
+                app::Symmetry::Symmetric(layout)
+            },
+            None if args.triangle.is_some() || args.diagonal.is_some() => fail!(
+                "--triangle and --diagonal only apply to symmetric boards. \
+                Pass --symmetry dimmed or --symmetry hidden as well."
+            ),
+            None => app::Symmetry::Asymmetric,
+        };
+
+        if let Err(e) = state.board_mut().set_symmetry(symmetry) {
+            fail!("Could not change the symmetry: {e}.");
+        }
+    }
+
+    if let Some(eye_movement) = args.eye_movement {
+        state.set_eye_movement(eye_movement);
+    }
+
+    if args.new {
+        if let Err(e) = save_state(&path, &state) {
+            fail!("Could not create {}: {e}", path.display());
+        }
+    }
+
+    raylib_rs_platform::inner_main(state, &path, &args.window);
 }
 
 /// Let's keep all the raylib specific stuff in one module to make it easier to add
@@ -138,9 +477,13 @@ fn main() {
 mod raylib_rs_platform {
     use super::{
         Storage,
+        save_state,
+        write_reports,
         source_spec,
         SPRITE_PIXELS_PER_TILE_SIDE,
         SPRITESHEET_BYTES,
+        FONT_BYTES,
+        BASE_GLYPH_RANGES,
         SAMPLING_SHADER,
         WINDOW_TITLE
     };
@@ -149,18 +492,93 @@ mod raylib_rs_platform {
         KeyboardKey::*,
         ffi::{
             LoadImageFromMemory,
-            MouseButton::MOUSE_LEFT_BUTTON,
+            MouseButton::{MOUSE_LEFT_BUTTON, MOUSE_RIGHT_BUTTON},
         },
         core::{
             drawing::{RaylibTextureModeExt, RaylibShaderModeExt},
             logging,
-            text::measure_text,
+            text::measure_text_ex,
         }
     };
 
     use ::core::{
         convert::TryInto,
     };
+    use std::collections::BTreeSet;
+
+    /// The size the glyphs are rendered at when the font is loaded. Text is
+    /// scaled from this size when drawn.
+    const FONT_LOAD_SIZE: i32 = 48;
+
+    /// Raylib fonts only have the glyphs for the characters asked for when
+    /// they are loaded, so this needs to be called again when a character
+    /// not in `glyphs` needs to be drawn.
+    fn load_font(glyphs: &BTreeSet<char>) -> Font {
+        let byte_count: i32 = FONT_BYTES.len()
+            .try_into()
+            .expect("(2^31)-1 bytes ought to be enough for anybody!");
+
+        let mut codepoints: Vec<i32> = glyphs.iter()
+            .map(|&c| c as i32)
+            .collect();
+
+        let codepoint_count: i32 = codepoints.len()
+            .try_into()
+            .expect("There are fewer than (2^31)-1 chars");
+
+        let file_type = b".ttf\0" as *const u8 as *const i8;
+
+        unsafe {
+            let font = ffi::LoadFontFromMemory(
+                file_type,
+                FONT_BYTES.as_ptr(),
+                byte_count,
+                FONT_LOAD_SIZE,
+                codepoints.as_mut_ptr(),
+                codepoint_count,
+            );
+
+            // The text is usually drawn smaller than it was loaded at, which
+            // looks jagged without filtering.
+            ffi::SetTextureFilter(
+                font.texture,
+                ffi::TextureFilter::TEXTURE_FILTER_BILINEAR as i32,
+            );
+
+            Font::from_raw(font)
+        }
+    }
+
+    /// The space between characters when drawing text.
+    const TEXT_SPACING: f32 = 1.;
+
+    /// How big to draw each kind of text. The constants were arrived at
+    /// through trial and error.
+    fn text_size(draw_wh: app::DrawWH, kind: app::draw::TextKind) -> f32 {
+        use app::draw::TextKind::*;
+        match kind {
+            UI | OneTile | TextBox | TextBoxWithCursor(_) => draw_wh.w * (1./48.),
+            // Shrunk for small cells on boards with a lot of labels.
+            CellLabel => draw_wh.w * (1./112.),
+        }
+    }
+
+    /// Every character that shows up in the board's labels and notes.
+    fn board_chars(board: &app::Board) -> BTreeSet<char> {
+        let mut chars = BTreeSet::new();
+
+        for axis in [app::Axis::Columns, app::Axis::Rows] {
+            for label in board.labels(axis) {
+                chars.extend(label.chars());
+            }
+        }
+
+        for (_, _, note) in board.notes() {
+            chars.extend(note.chars());
+        }
+
+        chars
+    }
 
     fn draw_wh(rl: &RaylibHandle) -> app::DrawWH {
         app::DrawWH {
@@ -169,7 +587,19 @@ mod raylib_rs_platform {
         }
     }
 
-    pub fn inner_main() {
+    pub fn inner_main(
+        mut state: app::State,
+        board_path: &std::path::Path,
+        window: &crate::cli::Window,
+    ) {
+        macro_rules! save {
+            () => {
+                if let Err(e) = save_state(board_path, &state) {
+                    eprintln!("Could not save board to {}: {e}", board_path.display());
+                }
+            }
+        }
+
         let (mut rl, thread) = {
             // TODO: Read display size ourselves, since while raylib tries to figure
             // out the right size if `0, 0` is passed, it sometimes gets the wrong
@@ -191,8 +621,10 @@ mod raylib_rs_platform {
             #[cfg(not(target_os = "linux"))]
             const H: i32 = 0;
 
+            let (w, h) = window.size.unwrap_or((W, H));
+
             raylib::init()
-            .size(W, H)
+            .size(w, h)
             .resizable()
             .title(WINDOW_TITLE)
             .build()
@@ -203,10 +635,17 @@ mod raylib_rs_platform {
         }
 
         rl.set_target_fps(60);
-        rl.toggle_fullscreen();
+        if !window.windowed {
+            rl.toggle_fullscreen();
+        }
 
-        // We need a reference to this so we can use `draw_text_rec`
-        let font = rl.get_font_default();
+        let mut glyphs: BTreeSet<char> = BASE_GLYPH_RANGES.iter()
+            .cloned()
+            .flatten()
+            .collect();
+        glyphs.extend(board_chars(state.board()));
+
+        let mut font = load_font(&glyphs);
 
         let spritesheet_img = {
             let byte_count: i32 = SPRITESHEET_BYTES.len()
@@ -260,7 +699,6 @@ mod raylib_rs_platform {
             RENDER_TARGET_SIZE
         ).unwrap();
 
-        let mut state = app::State::default();
         let mut commands = Storage(Vec::with_capacity(1024));
 
         macro_rules! get_cursor_xy {
@@ -274,12 +712,26 @@ mod raylib_rs_platform {
             }}
         }
 
+        macro_rules! text_width {
+            () => {
+                &|text: &str, kind| {
+                    measure_text_ex(
+                        &font,
+                        text,
+                        text_size(draw_wh(&rl), kind),
+                        TEXT_SPACING,
+                    ).x
+                }
+            }
+        }
+
         // generate the commands for the first frame
         app::update(
             &mut state,
             &mut commands,
             0,
             <_>::default(),
+            text_width!(),
             get_cursor_xy!(),
             draw_wh(&rl),
         );
@@ -290,8 +742,21 @@ mod raylib_rs_platform {
         const CURSOR: Color = Color{ r: 0xde, g: 0x49, b: 0x49, a: 255 };
         const NO_TINT: Color = WHITE;
         const OUTLINE: Color = WHITE;
-
-        let mut backspace_repeat_timer = 0;
+        const DIM: Color = Color{ a: 0xaa, ..BACKGROUND };
+        const FOCUS: Color = Color{ r: 0xff, g: 0xb9, b: 0x37, a: 255 };
+        const SELECTED: Color = Color{ a: 0x66, ..FOCUS };
+        const CROSSHAIR: Color = Color{ a: 0x33, ..WHITE };
+        const BAR_EMPTY: Color = Color{ r: 0x44, g: 0x44, b: 0x44, a: 255 };
+        // These match the colours of the tiles in the spritesheet.
+        const PASSES: Color = Color{ r: 0x33, g: 0x52, b: 0xe1, a: 255 };
+        const FAILS: Color = Color{ r: 0xc8, g: 0x32, b: 0x3c, a: 255 };
+        const BLOCKED: Color = Color{ r: 0xe0, g: 0x7b, b: 0x24, a: 255 };
+        const NEEDS_RECHECK: Color = Color{ r: 0xe0, g: 0xc2, b: 0x24, a: 255 };
+        const NOT_APPLICABLE: Color = Color{ r: 0x77, g: 0x77, b: 0x77, a: 255 };
+        // Unchecked tiles are the background colour, which would not show up.
+        const UNCHECKED: Color = WHITE;
+
+        let mut key_repeat_timer = 0;
 
         let mut show_stats = false;
         use std::time::Instant;
@@ -343,37 +808,125 @@ mod raylib_rs_platform {
                 show_stats = !show_stats;
             }
 
+            let is_ctrl_down = rl.is_key_down(KEY_LEFT_CONTROL)
+                || rl.is_key_down(KEY_RIGHT_CONTROL);
+            let is_shift_down = rl.is_key_down(KEY_LEFT_SHIFT)
+                || rl.is_key_down(KEY_RIGHT_SHIFT);
+
+            if is_ctrl_down && rl.is_key_pressed(KEY_S) {
+                save!();
+            }
+
+            // Reports are usually pasted somewhere, so put the Markdown one
+            // on the clipboard too.
+            if is_ctrl_down && rl.is_key_pressed(KEY_R) {
+                match write_reports(board_path, &state) {
+                    Ok(markdown) => if let Err(e) = rl.set_clipboard_text(&markdown) {
+                        eprintln!("Could not copy the report to the clipboard: {e}");
+                    },
+                    Err(e) => eprintln!(
+                        "Could not write reports next to {}: {e}",
+                        board_path.display()
+                    ),
+                }
+            }
+
             let mut text_input = app::TextInput::default();
             {
-                let mut byte_index = 0;
+                let chars = &mut text_input.chars;
+                let mut char_index = 0;
                 let mut key = unsafe{ ffi::GetCharPressed() };
 
-                while key > 0 && byte_index < text_input.len() {
-                    dbg!(key);
-                    text_input[byte_index] = (key & 0xff) as u8;
-                    byte_index += 1;
-    
+                while key > 0 && char_index < chars.len() {
+                    // Raylib gives us Unicode codepoints.
+                    if let Some(c) = char::from_u32(key as u32) {
+                        chars[char_index] = c;
+                        char_index += 1;
+                    }
+
                     // Check next character in the queue
                     key = unsafe{ ffi::GetCharPressed() };
                 }
 
-
                 const KEY_REPEAT_FRAMES: u8 = 8;
-                if backspace_repeat_timer < KEY_REPEAT_FRAMES {
-                    backspace_repeat_timer += 1;
+                if key_repeat_timer < KEY_REPEAT_FRAMES {
+                    key_repeat_timer += 1;
+                }
+                let can_repeat = key_repeat_timer >= KEY_REPEAT_FRAMES;
+
+                macro_rules! pressed_or_repeated {
+                    ($key: expr) => {{
+                        let fired = rl.is_key_pressed($key)
+                            || (rl.is_key_down($key) && can_repeat);
+                        if fired {
+                            key_repeat_timer = 0;
+                        }
+                        fired
+                    }}
+                }
+
+                for (key, c) in [(KEY_BACKSPACE, '\u{8}'), (KEY_DELETE, '\u{7f}')] {
+                    if pressed_or_repeated!(key) && char_index < chars.len() {
+                        chars[char_index] = c;
+                        char_index += 1;
+                    }
                 }
 
-                if rl.is_key_pressed(KEY_BACKSPACE)
-                || (
-                    rl.is_key_down(KEY_BACKSPACE)
-                    && backspace_repeat_timer >= KEY_REPEAT_FRAMES
-                ) {
-                    if byte_index < text_input.len() {
-                        // Backspace in ASCII
-                        text_input[byte_index] = 8;
-                        backspace_repeat_timer = 0;
+                for (key, text_key) in [
+                    (KEY_LEFT, app::TEXT_LEFT),
+                    (KEY_RIGHT, app::TEXT_RIGHT),
+                    (KEY_HOME, app::TEXT_HOME),
+                    (KEY_END, app::TEXT_END),
+                    (KEY_TAB, app::TEXT_TAB),
+                ] {
+                    if pressed_or_repeated!(key) {
+                        text_input.keys |= text_key;
                     }
                 }
+
+                for (key, text_key) in [
+                    (KEY_ENTER, app::TEXT_ENTER),
+                    (KEY_KP_ENTER, app::TEXT_ENTER),
+                    (KEY_ESCAPE, app::TEXT_ESCAPE),
+                ] {
+                    if rl.is_key_pressed(key) {
+                        text_input.keys |= text_key;
+                    }
+                }
+
+                if is_shift_down {
+                    text_input.keys |= app::TEXT_SHIFT_DOWN;
+                }
+
+                if is_ctrl_down {
+                    text_input.keys |= app::TEXT_CTRL_DOWN;
+
+                    for (key, text_key) in [
+                        (KEY_A, app::TEXT_SELECT_ALL),
+                        (KEY_C, app::TEXT_COPY),
+                        (KEY_X, app::TEXT_CUT),
+                    ] {
+                        if rl.is_key_pressed(key) {
+                            text_input.keys |= text_key;
+                        }
+                    }
+
+                    if rl.is_key_pressed(KEY_V) {
+                        text_input.pasted = rl.get_clipboard_text().ok();
+                    }
+                }
+
+                let new_glyphs: BTreeSet<char> = text_input.chars[..char_index]
+                    .iter()
+                    .copied()
+                    .chain(text_input.pasted.iter().flat_map(|s| s.chars()))
+                    .filter(|c| !glyphs.contains(c))
+                    .collect();
+
+                if !new_glyphs.is_empty() {
+                    glyphs.extend(new_glyphs);
+                    font = load_font(&glyphs);
+                }
             }
 
             let mut input_flags = 0;
@@ -386,38 +939,75 @@ mod raylib_rs_platform {
                 input_flags |= app::INPUT_INTERACT_DOWN;
             }
 
-            if rl.is_key_down(KEY_UP) || rl.is_key_down(KEY_W) {
+            // With Ctrl held, letters are shortcuts, like Ctrl+S to save and
+            // Ctrl+A to select all, instead of moving the eye or acting on the
+            // board.
+            let letters = !is_ctrl_down;
+
+            if rl.is_key_down(KEY_UP) || (letters && rl.is_key_down(KEY_W)) {
                 input_flags |= app::INPUT_UP_DOWN;
             }
 
-            if rl.is_key_down(KEY_DOWN) || rl.is_key_down(KEY_S) {
+            if rl.is_key_down(KEY_DOWN) || (letters && rl.is_key_down(KEY_S)) {
                 input_flags |= app::INPUT_DOWN_DOWN;
             }
 
-            if rl.is_key_down(KEY_LEFT) || rl.is_key_down(KEY_A) {
+            if rl.is_key_down(KEY_LEFT) || (letters && rl.is_key_down(KEY_A)) {
                 input_flags |= app::INPUT_LEFT_DOWN;
             }
 
-            if rl.is_key_down(KEY_RIGHT) || rl.is_key_down(KEY_D) {
+            if rl.is_key_down(KEY_RIGHT) || (letters && rl.is_key_down(KEY_D)) {
                 input_flags |= app::INPUT_RIGHT_DOWN;
             }
 
-            if rl.is_key_pressed(KEY_UP) || rl.is_key_pressed(KEY_W) {
+            if rl.is_key_pressed(KEY_UP) || (letters && rl.is_key_pressed(KEY_W)) {
                 input_flags |= app::INPUT_UP_PRESSED;
             }
 
-            if rl.is_key_pressed(KEY_DOWN) || rl.is_key_pressed(KEY_S) {
+            if rl.is_key_pressed(KEY_DOWN) || (letters && rl.is_key_pressed(KEY_S)) {
                 input_flags |= app::INPUT_DOWN_PRESSED;
             }
 
-            if rl.is_key_pressed(KEY_LEFT) || rl.is_key_pressed(KEY_A) {
+            if rl.is_key_pressed(KEY_LEFT) || (letters && rl.is_key_pressed(KEY_A)) {
                 input_flags |= app::INPUT_LEFT_PRESSED;
             }
 
-            if rl.is_key_pressed(KEY_RIGHT) || rl.is_key_pressed(KEY_D) {
+            if rl.is_key_pressed(KEY_RIGHT) || (letters && rl.is_key_pressed(KEY_D)) {
                 input_flags |= app::INPUT_RIGHT_PRESSED;
             }
 
+            if letters && rl.is_key_pressed(KEY_M) {
+                input_flags |= app::INPUT_SYMMETRY_PRESSED;
+            }
+
+            if letters && rl.is_key_pressed(KEY_T) {
+                input_flags |= app::INPUT_TRIANGLE_PRESSED;
+            }
+
+            if letters && rl.is_key_pressed(KEY_I) {
+                input_flags |= app::INPUT_DIAGONAL_PRESSED;
+            }
+
+            if letters && rl.is_key_pressed(KEY_N) {
+                input_flags |= app::INPUT_NOTE_PRESSED;
+            }
+
+            if letters && rl.is_key_pressed(KEY_C) {
+                input_flags |= app::INPUT_CHECK_ALL_PRESSED;
+            }
+
+            if rl.is_key_pressed(KEY_DELETE) || rl.is_key_pressed(KEY_BACKSPACE) {
+                input_flags |= app::INPUT_CLEAR_ALL_PRESSED;
+            }
+
+            if is_ctrl_down && rl.is_key_pressed(KEY_Z) {
+                input_flags |= if is_shift_down {
+                    app::INPUT_REDO_PRESSED
+                } else {
+                    app::INPUT_UNDO_PRESSED
+                };
+            }
+
             if rl.is_mouse_button_pressed(MOUSE_LEFT_BUTTON)
             || rl.is_mouse_button_released(MOUSE_LEFT_BUTTON) {
                 input_flags |= app::INPUT_LEFT_MOUSE_CHANGED;
@@ -427,6 +1017,14 @@ mod raylib_rs_platform {
                 input_flags |= app::INPUT_LEFT_MOUSE_DOWN;
             }
 
+            if is_shift_down {
+                input_flags |= app::INPUT_SHIFT_DOWN;
+            }
+
+            if rl.is_mouse_button_pressed(MOUSE_RIGHT_BUTTON) {
+                input_flags |= app::INPUT_RIGHT_MOUSE_PRESSED;
+            }
+
             current_stats.input_gather.end = Instant::now();
             current_stats.update.start = current_stats.input_gather.end;
 
@@ -435,10 +1033,25 @@ mod raylib_rs_platform {
                 &mut commands,
                 input_flags,
                 text_input,
+                text_width!(),
                 get_cursor_xy!(),
                 draw_wh(&rl)
             );
 
+            // Escape cancels typing, so it should only close the window when
+            // nothing is being typed into.
+            rl.set_exit_key(if state.is_editing_text() {
+                None
+            } else {
+                Some(KEY_ESCAPE)
+            });
+
+            if let Some(copied) = state.take_copied_text() {
+                if let Err(e) = rl.set_clipboard_text(&copied) {
+                    eprintln!("Could not copy to the clipboard: {e}");
+                }
+            }
+
             current_stats.update.end = Instant::now();
             current_stats.render.start = current_stats.update.end;
 
@@ -484,13 +1097,6 @@ mod raylib_rs_platform {
                     height: SPRITE_PIXELS_PER_TILE_SIDE,
                 };
 
-                let tile_base_render_rect = Rectangle {
-                    x: 0.,
-                    y: 0.,
-                    width: sizes.tile_side_length,
-                    height: sizes.tile_side_length,
-                };
-
                 // I don't know why the texture lookup seems to be offset by these
                 // amounts, but it seems to be.
                 const X_SOURCE_FUDGE: f32 = -0.25;
@@ -503,14 +1109,15 @@ mod raylib_rs_platform {
                             let spec = source_spec(s.sprite);
 
                             let origin = Vector2 {
-                                x: (tile_base_render_rect.width / 2.).round(),
-                                y: (tile_base_render_rect.height / 2.).round(),
+                                x: (s.wh.w / 2.).round(),
+                                y: (s.wh.h / 2.).round(),
                             };
 
                             let render_rect = Rectangle {
                                 x: s.xy.x + origin.x,
                                 y: s.xy.y + origin.y,
-                                ..tile_base_render_rect
+                                width: s.wh.w,
+                                height: s.wh.h,
                             };
 
                             let source_rect = Rectangle {
@@ -531,17 +1138,14 @@ mod raylib_rs_platform {
                         Text(t) => {
                             macro_rules! draw_text {
                                 ($rect: expr, $size: expr $(,)?) => {
-                                    draw_text!($rect, $size, &t.text, TEXT);
-                                };
-                                ($rect: expr, $size: expr, $text: expr, $colour: expr $(,)?) => {
                                     shader_d.draw_text_rec(
                                         &font,
-                                        $text,
+                                        &t.text,
                                         $rect,
                                         $size,
-                                        1.,
+                                        TEXT_SPACING,
                                         true, // word_wrap
-                                        $colour,
+                                        TEXT,
                                     );
                                 }
                             }
@@ -558,6 +1162,7 @@ mod raylib_rs_platform {
                             }
 
                             use app::draw::TextKind;
+                            let size = text_size(sizes.draw_wh, t.kind);
                             match t.kind {
                                 TextKind::UI => {
                                     draw_text!(
@@ -567,74 +1172,128 @@ mod raylib_rs_platform {
                                             width: t.wh.w,
                                             height: t.wh.h,
                                         },
-                                        // Constant arrived at through trial 
-                                        // and error.
-                                        sizes.draw_wh.w * (1./48.),
-                                    );
-                                },
-                                TextKind::OneTile => {
-                                    draw_text!(
-                                        margin_rect!(),
-                                        // Constant arrived at through trial 
-                                        // and error.
-                                        sizes.draw_wh.w * (1./48.),
+                                        size,
                                     );
                                 },
-                                TextKind::TextBox => {
+                                TextKind::OneTile
+                                | TextKind::TextBox => {
                                     draw_text!(
                                         margin_rect!(),
-                                        // Constant arrived at through trial 
-                                        // and error.
-                                        sizes.draw_wh.w * (1./48.),
+                                        size,
                                     );
                                 },
-                                TextKind::TextBoxWithCursor => {
+                                TextKind::TextBoxWithCursor(cursor) => {
                                     let rect = margin_rect!();
 
-                                    // Constant arrived at through trial 
-                                    // and error.
-                                    let size = sizes.draw_wh.w * (1./48.);
+                                    let x_at = |i: usize| {
+                                        rect.x + measure_text_ex(
+                                            &font,
+                                            &t.text[..i],
+                                            size,
+                                            TEXT_SPACING,
+                                        ).x
+                                    };
+
+                                    if cursor.caret != cursor.anchor {
+                                        let start_x = x_at(cursor.caret.min(cursor.anchor));
+                                        let end_x = x_at(cursor.caret.max(cursor.anchor));
+
+                                        shader_d.draw_rectangle_rec(
+                                            Rectangle {
+                                                x: start_x,
+                                                y: rect.y,
+                                                width: end_x - start_x,
+                                                height: size,
+                                            },
+                                            SELECTED,
+                                        );
+                                    }
 
                                     draw_text!(
                                         rect,
                                         size,
                                     );
 
-                                    let size_pixels = size.floor() as i32;
-
-                                    let measured_width = 
-                                        measure_text(&t.text, size_pixels)
-                                        as f32;
-
-                                    let width = measured_width
-                                    // No idea why it seems to be off by this amount
-                                    - (
-                                        t.text.chars().count() as f32
-                                        * 3.
-                                    )
-                                    + sizes.text_box_margin;
-
-                                    // TODO make this cursor blink
-                                    draw_text!(
-                                        Rectangle {
-                                            x: rect.x + width,
-                                            ..rect
-                                        },
-                                        size,
-                                        "_",
-                                        CURSOR,
-                                    );
+                                    if cursor.is_caret_shown {
+                                        shader_d.draw_rectangle_rec(
+                                            Rectangle {
+                                                x: x_at(cursor.caret),
+                                                y: rect.y,
+                                                width: (size / 16.).max(1.),
+                                                height: size,
+                                            },
+                                            CURSOR,
+                                        );
+                                    }
                                 },
                                 TextKind::CellLabel => {
                                     draw_text!(
                                         margin_rect!(),
-                                        // Constant arrived at through trial 
-                                        // and error.
-                                        sizes.draw_wh.w * (1./112.),
+                                        size.min(t.wh.h),
                                     );
                                 }
                             };
                         }
+                        Overlay(o) => {
+                            use app::draw::OverlayKind;
+                            match o.kind {
+                                OverlayKind::Dim
+                                | OverlayKind::Selected
+                                | OverlayKind::Crosshair => {
+                                    shader_d.draw_rectangle_v(
+                                        Vector2 { x: o.xy.x, y: o.xy.y },
+                                        Vector2 { x: o.wh.w, y: o.wh.h },
+                                        match o.kind {
+                                            OverlayKind::Dim => DIM,
+                                            OverlayKind::Selected => SELECTED,
+                                            _ => CROSSHAIR,
+                                        },
+                                    );
+                                },
+                                OverlayKind::Focus => {
+                                    shader_d.draw_rectangle_lines_ex(
+                                        Rectangle {
+                                            x: o.xy.x,
+                                            y: o.xy.y,
+                                            width: o.wh.w,
+                                            height: o.wh.h,
+                                        },
+                                        // Thin enough to leave most of a
+                                        // small tile visible.
+                                        (o.wh.w / 12.).ceil().max(1.) as i32,
+                                        FOCUS,
+                                    );
+                                },
+                            }
+                        }
+                        Bar(b) => {
+                            use app::{draw::BarKind, Status};
+                            let filled = match b.kind {
+                                BarKind::Checked => PASSES,
+                                BarKind::Status(status) => match status {
+                                    Status::Unchecked => UNCHECKED,
+                                    Status::Passes => PASSES,
+                                    Status::Fails => FAILS,
+                                    Status::Blocked => BLOCKED,
+                                    Status::NeedsRecheck => NEEDS_RECHECK,
+                                    Status::NotApplicable => NOT_APPLICABLE,
+                                },
+                            };
+
+                            shader_d.draw_rectangle_v(
+                                Vector2 { x: b.xy.x, y: b.xy.y },
+                                Vector2 { x: b.wh.w, y: b.wh.h },
+                                BAR_EMPTY,
+                            );
+                            shader_d.draw_rectangle_v(
+                                Vector2 { x: b.xy.x, y: b.xy.y },
+                                Vector2 {
+                                    x: b.wh.w * b.fraction.clamp(0., 1.),
+                                    y: b.wh.h,
+                                },
+                                filled,
+                            );
+                        }
                     }
                 }
 
@@ -685,6 +1344,8 @@ mod raylib_rs_platform {
 
             prev_stats = current_stats;
         }
+
+        save!();
     }
 
 }